@@ -0,0 +1,499 @@
+//! Game content: items, upgrades and events.
+//!
+//! Designers edit the RON files in `engine/content/`. They're embedded at
+//! build time and parsed into a `Registry` the first time anything asks for
+//! it. Specs refer to each other by `key`, and every reference is checked
+//! on load, so a typo is a startup error rather than a silently dead item
+//! or an event that never fires.
+//!
+//! A spec's `key` is its identity: specs compare and hash by it, and save
+//! files store it instead of the name. Renaming a key that has shipped
+//! orphans whatever old saves recorded under it, so pick a new name for
+//! the player instead, or migrate the saves in the `save` module.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use super::events::{Choice, Effect, EventSpec, Trigger};
+use super::items::ItemSpec;
+use super::units::*;
+use super::upgrades::{UpgradeEffect, UpgradeSpec};
+use super::CustomerKind;
+
+const ITEMS: &str = include_str!("../content/items.ron");
+const UPGRADES: &str = include_str!("../content/upgrades.ron");
+const EVENTS: &str = include_str!("../content/events.ron");
+
+/// Events the engine fires by itself, so the content must define them.
+const BUILTIN_EVENTS: &[&str] = &["welcome", "away_report", "overflow", "extinction"];
+
+lazy_static! {
+    static ref REGISTRY: Registry =
+        Registry::load().unwrap_or_else(|e| panic!("invalid game content: {}", e));
+}
+
+/// The content embedded in the engine.
+pub fn registry() -> &'static Registry {
+    &REGISTRY
+}
+
+pub struct Registry {
+    pub items: Vec<&'static ItemSpec>,
+    pub upgrades: Vec<&'static UpgradeSpec>,
+    pub events: Vec<&'static EventSpec>,
+}
+
+#[derive(Debug)]
+pub enum ContentError {
+    Parse { file: &'static str, message: String },
+    UnknownItem { upgrade: String, item: String },
+    UnknownUpgrade { upgrade: String, requires: String },
+    UnknownTrigger { event: String, key: String },
+    UnknownEffect { event: String, key: String },
+    ToastWithChoices { event: String },
+    NoFreeChoice { event: String },
+    CostlyEffect { event: String },
+    RandomWithChoices { event: String },
+    InvalidChance { event: String },
+    MissingEvent { key: &'static str },
+    InvalidCurve { item: String },
+    MissingKey { file: &'static str },
+    DuplicateKey { file: &'static str, key: String },
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContentError::Parse { file, message } => write!(f, "{}: {}", file, message),
+            ContentError::UnknownItem { upgrade, item } => write!(
+                f,
+                "upgrade `{}` has an effect on unknown item `{}`",
+                upgrade, item
+            ),
+            ContentError::UnknownUpgrade { upgrade, requires } => write!(
+                f,
+                "upgrade `{}` requires `{}`, which isn't defined before it",
+                upgrade, requires
+            ),
+            ContentError::UnknownTrigger { event, key } => write!(
+                f,
+                "event `{}` is triggered by unknown item or upgrade `{}`",
+                event, key
+            ),
+            ContentError::UnknownEffect { event, key } => write!(
+                f,
+                "event `{}` has an effect on `{}`, which isn't an item, upgrade or event defined before it",
+                event, key
+            ),
+            ContentError::ToastWithChoices { event } => write!(
+                f,
+                "event `{}` is a toast, which can't be answered, but has choices",
+                event
+            ),
+            ContentError::NoFreeChoice { event } => write!(
+                f,
+                "event `{}` has no choice that's free, a player without souls couldn't answer it",
+                event
+            ),
+            ContentError::CostlyEffect { event } => write!(
+                f,
+                "event `{}` takes souls when it fires, only choices can, since the player may not have them",
+                event
+            ),
+            ContentError::RandomWithChoices { event } => write!(
+                f,
+                "event `{}` is random, so it can fire again and forget its answer, but has choices",
+                event
+            ),
+            ContentError::InvalidChance { event } => {
+                write!(f, "event `{}` has a chance outside of [0, 1]", event)
+            }
+            ContentError::MissingEvent { key } => {
+                write!(f, "event `{}` is used by the engine but isn't defined", key)
+            }
+            ContentError::InvalidCurve { item } => {
+                write!(f, "item `{}` has a cost curve that goes down", item)
+            }
+            ContentError::MissingKey { file } => write!(f, "{}: spec without a key", file),
+            ContentError::DuplicateKey { file, key } => {
+                write!(f, "{}: key `{}` is defined more than once", file, key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContentError {}
+
+/// What an upgrade looks like in `upgrades.ron`, before references are
+/// resolved.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UpgradeDef {
+    key: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    cost: Souls,
+    #[serde(default)]
+    effects: Vec<UpgradeEffectDef>,
+    requires: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UpgradeEffectDef {
+    item: String,
+    spc_mod: Option<f64>,
+    spt_mod: Option<f64>,
+    interest_mod: Option<f64>,
+}
+
+/// What an event looks like in `events.ron`, before references are
+/// resolved.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EventDef {
+    key: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    triggers: Vec<TriggerDef>,
+    #[serde(default)]
+    choices: Vec<ChoiceDef>,
+    #[serde(default)]
+    toast: bool,
+    #[serde(default)]
+    chance: f64,
+    #[serde(default)]
+    effects: Vec<EffectDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChoiceDef {
+    key: String,
+    label: String,
+    #[serde(default)]
+    effects: Vec<EffectDef>,
+}
+
+/// See `Effect`, items, upgrades and events are given by key.
+#[derive(Deserialize)]
+enum EffectDef {
+    Souls(Souls),
+    Goodness(f64),
+    BirthRate(f64),
+    RevealItem(String),
+    RevealUpgrade(String),
+    Event(String),
+    Kill(f64),
+    Births(f64),
+    Rates {
+        #[serde(default)]
+        birth: f64,
+        #[serde(default)]
+        death: f64,
+        months: i64,
+    },
+}
+
+/// See `Trigger`, items and upgrades are given by key.
+#[derive(Deserialize)]
+enum TriggerDef {
+    ItemQuantity { item: String, quantity: i64 },
+    Souls(Souls),
+    Month(i64),
+    PopulationBelow(Souls),
+    UpgradeBought(String),
+    Favor { realm: CustomerKind, min: Souls },
+    Chose { event: String, choice: String },
+}
+
+impl Registry {
+    fn load() -> Result<Self, ContentError> {
+        Self::parse(ITEMS, UPGRADES, EVENTS)
+    }
+
+    /// Builds a registry out of RON sources. Specs are leaked, since
+    /// content lives for as long as the game does anyway.
+    pub fn parse(items: &str, upgrades: &str, events: &str) -> Result<Self, ContentError> {
+        let items: Vec<&'static ItemSpec> = parse::<ItemSpec>("items.ron", items)?
+            .into_iter()
+            .map(|mut spec| {
+                spec.desc = dedent(&spec.desc);
+                &*Box::leak(Box::new(spec))
+            })
+            .collect();
+        check_unique("items.ron", items.iter().map(|spec| &spec.key))?;
+        if let Some(spec) = items.iter().find(|spec| !spec.curve.is_valid()) {
+            return Err(ContentError::InvalidCurve {
+                item: spec.key.clone(),
+            });
+        }
+
+        let mut reg = Self {
+            items,
+            upgrades: Vec::new(),
+            events: Vec::new(),
+        };
+
+        for def in parse::<UpgradeDef>("upgrades.ron", upgrades)? {
+            let spec = reg.resolve_upgrade(def)?;
+            reg.upgrades.push(Box::leak(Box::new(spec)));
+        }
+        check_unique("upgrades.ron", reg.upgrades.iter().map(|spec| &spec.key))?;
+
+        for def in parse::<EventDef>("events.ron", events)? {
+            let spec = reg.resolve_event(def)?;
+            reg.events.push(Box::leak(Box::new(spec)));
+        }
+        check_unique("events.ron", reg.events.iter().map(|spec| &spec.key))?;
+        for &key in BUILTIN_EVENTS {
+            if reg.event(key).is_none() {
+                return Err(ContentError::MissingEvent { key });
+            }
+        }
+
+        Ok(reg)
+    }
+
+    fn resolve_upgrade(&self, def: UpgradeDef) -> Result<UpgradeSpec, ContentError> {
+        let UpgradeDef {
+            key,
+            name,
+            desc,
+            cost,
+            effects: defs,
+            requires,
+        } = def;
+
+        let mut effects = Vec::new();
+        for fx in defs {
+            let spec = self
+                .item(&fx.item)
+                .ok_or_else(|| ContentError::UnknownItem {
+                    upgrade: key.clone(),
+                    item: fx.item.clone(),
+                })?;
+            effects.push(UpgradeEffect {
+                spec,
+                spc_mod: fx.spc_mod,
+                spt_mod: fx.spt_mod,
+                interest_mod: fx.interest_mod,
+            });
+        }
+
+        let requires = match requires {
+            Some(req) => Some(
+                self.upgrade(&req)
+                    .ok_or_else(|| ContentError::UnknownUpgrade {
+                        upgrade: key.clone(),
+                        requires: req.clone(),
+                    })?,
+            ),
+            None => None,
+        };
+
+        Ok(UpgradeSpec {
+            key,
+            name,
+            desc: dedent(&desc),
+            cost,
+            effects,
+            requires,
+        })
+    }
+
+    fn resolve_event(&self, def: EventDef) -> Result<EventSpec, ContentError> {
+        let unknown = |key: &str| ContentError::UnknownTrigger {
+            event: def.key.clone(),
+            key: key.to_owned(),
+        };
+
+        let mut triggers = Vec::new();
+        for t in &def.triggers {
+            triggers.push(match t {
+                TriggerDef::ItemQuantity { item, quantity } => Trigger::ItemQuantity {
+                    item: self.item(item).ok_or_else(|| unknown(item))?,
+                    quantity: *quantity,
+                },
+                TriggerDef::Souls(min) => Trigger::Souls(*min),
+                TriggerDef::Month(month) => Trigger::Month(*month),
+                TriggerDef::PopulationBelow(max) => Trigger::PopulationBelow(*max),
+                TriggerDef::UpgradeBought(upgrade) => {
+                    Trigger::UpgradeBought(self.upgrade(upgrade).ok_or_else(|| unknown(upgrade))?)
+                }
+                TriggerDef::Favor { realm, min } => Trigger::Favor {
+                    realm: *realm,
+                    min: *min,
+                },
+                TriggerDef::Chose { event, choice } => {
+                    let spec = self.event(event).ok_or_else(|| unknown(event))?;
+                    if !spec.choices.iter().any(|c| &c.key == choice) {
+                        return Err(unknown(&format!("{}.{}", event, choice)));
+                    }
+                    Trigger::Chose {
+                        event: spec,
+                        choice: choice.clone(),
+                    }
+                }
+            });
+        }
+
+        let mut effects = Vec::new();
+        for fx in &def.effects {
+            effects.push(self.resolve_effect(&def.key, fx)?);
+        }
+        let costly = |fx: &Effect| match fx {
+            Effect::Souls(q) => q.is_negative(),
+            _ => false,
+        };
+        if effects.iter().any(costly) {
+            return Err(ContentError::CostlyEffect { event: def.key });
+        }
+
+        let mut choices = Vec::new();
+        for c in &def.choices {
+            let mut effects = Vec::new();
+            for fx in &c.effects {
+                effects.push(self.resolve_effect(&def.key, fx)?);
+            }
+            choices.push(Choice {
+                key: c.key.clone(),
+                label: c.label.clone(),
+                effects,
+            });
+        }
+        check_unique("events.ron", choices.iter().map(|c| &c.key))?;
+        if def.toast && !choices.is_empty() {
+            return Err(ContentError::ToastWithChoices { event: def.key });
+        }
+        if !choices.is_empty() && choices.iter().all(|c| c.cost() > Souls::ZERO) {
+            return Err(ContentError::NoFreeChoice { event: def.key });
+        }
+        if !(0.0..=1.0).contains(&def.chance) {
+            return Err(ContentError::InvalidChance { event: def.key });
+        }
+        if def.chance > 0.0 && !choices.is_empty() {
+            return Err(ContentError::RandomWithChoices { event: def.key });
+        }
+
+        Ok(EventSpec {
+            desc: dedent(&def.desc),
+            key: def.key,
+            name: def.name,
+            triggers,
+            choices,
+            toast: def.toast,
+            chance: def.chance,
+            effects,
+        })
+    }
+
+    fn resolve_effect(&self, event: &str, def: &EffectDef) -> Result<Effect, ContentError> {
+        let unknown = |key: &str| ContentError::UnknownEffect {
+            event: event.to_owned(),
+            key: key.to_owned(),
+        };
+
+        Ok(match def {
+            EffectDef::Souls(q) => Effect::Souls(*q),
+            EffectDef::Goodness(d) => Effect::Goodness(*d),
+            EffectDef::BirthRate(d) => Effect::BirthRate(*d),
+            EffectDef::RevealItem(item) => {
+                Effect::RevealItem(self.item(item).ok_or_else(|| unknown(item))?)
+            }
+            EffectDef::RevealUpgrade(upgrade) => {
+                Effect::RevealUpgrade(self.upgrade(upgrade).ok_or_else(|| unknown(upgrade))?)
+            }
+            EffectDef::Event(event) => {
+                Effect::Event(self.event(event).ok_or_else(|| unknown(event))?)
+            }
+            EffectDef::Kill(ratio) => Effect::Kill(ratio.clamp(0.0, 1.0)),
+            EffectDef::Births(ratio) => Effect::Births(ratio.max(0.0)),
+            EffectDef::Rates {
+                birth,
+                death,
+                months,
+            } => Effect::Rates {
+                birth: *birth,
+                death: *death,
+                months: *months,
+            },
+        })
+    }
+
+    pub fn item(&self, key: &str) -> Option<&'static ItemSpec> {
+        self.items.iter().cloned().find(|spec| spec.key == key)
+    }
+
+    pub fn upgrade(&self, key: &str) -> Option<&'static UpgradeSpec> {
+        self.upgrades.iter().cloned().find(|spec| spec.key == key)
+    }
+
+    pub fn event(&self, key: &str) -> Option<&'static EventSpec> {
+        self.events.iter().cloned().find(|spec| spec.key == key)
+    }
+
+    /// One of the events the engine fires by itself. Those are checked
+    /// on load, so this never fails.
+    pub fn builtin_event(&self, key: &'static str) -> &'static EventSpec {
+        debug_assert!(BUILTIN_EVENTS.contains(&key));
+        self.event(key)
+            .unwrap_or_else(|| panic!("builtin event `{}` missing", key))
+    }
+}
+
+fn parse<T>(file: &'static str, source: &str) -> Result<Vec<T>, ContentError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    ron::de::from_str(source).map_err(|e| ContentError::Parse {
+        file,
+        message: e.to_string(),
+    })
+}
+
+/// Specs are told apart by their key alone, so it has to be there and
+/// unique per kind.
+fn check_unique<'a>(
+    file: &'static str,
+    keys: impl Iterator<Item = &'a String>,
+) -> Result<(), ContentError> {
+    let mut seen = HashSet::new();
+    for key in keys {
+        if key.is_empty() {
+            return Err(ContentError::MissingKey { file });
+        }
+        if !seen.insert(key) {
+            return Err(ContentError::DuplicateKey {
+                file,
+                key: key.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Strips the indentation shared by all non-blank lines, along with
+/// leading and trailing blank lines, so multi-line descriptions can be
+/// indented like the RON around them.
+fn dedent(text: &str) -> String {
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    text.lines()
+        .map(|line| line.get(indent..).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_owned()
+}
@@ -0,0 +1,244 @@
+//! How prices go up as you buy more of an item.
+
+use std::cmp;
+
+use serde::Deserialize;
+
+use super::units::*;
+
+/// Sums below this add up the prices `ith` charges one by one, so buying
+/// in bulk costs exactly what buying one at a time would. Past it, amounts
+/// aren't exact anyway, and a closed form does.
+const EXACT_TOTAL: Souls = Souls::new(1_000_000_000_000_000);
+
+/// ...unless that takes more than this many prices (runs of equal prices
+/// count as one), in which case the rest is approximated too. Whole
+/// prices still come out exact, fractional ones can cost up to a soul
+/// more per unit than they would one by one, never less.
+const MAX_EXACT_TERMS: i64 = 10_000;
+
+/// Nobody owns more of anything than this, which keeps quantities well
+/// away from overflowing.
+const MAX_UNITS: i64 = i64::MAX / 4;
+
+/// Price of the `i`th unit bought (counting from 0), relative to the
+/// item's base `cost`. Prices never go down, so the more you own, the
+/// more the next one costs.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum CostCurve {
+    /// `cost * factor^i`
+    Exponential(f64),
+    /// `cost * (i + 1)^power`
+    Polynomial(f64),
+    /// `cost * factor^(i / every)`: the same price for `every` units,
+    /// then a jump.
+    Stepped { every: i64, factor: f64 },
+    /// Always `cost`, for one-shots.
+    Flat,
+}
+
+impl Default for CostCurve {
+    fn default() -> Self {
+        CostCurve::Exponential(1.12)
+    }
+}
+
+impl CostCurve {
+    /// Whether prices stay finite and never go down.
+    pub fn is_valid(self) -> bool {
+        match self {
+            CostCurve::Exponential(factor) => factor.is_finite() && factor >= 1.0,
+            CostCurve::Polynomial(power) => power.is_finite() && power >= 0.0,
+            CostCurve::Stepped { every, factor } => {
+                every > 0 && factor.is_finite() && factor >= 1.0
+            }
+            CostCurve::Flat => true,
+        }
+    }
+
+    /// Price of unit `i`, fractions included.
+    fn price(self, cost: Souls, i: i64) -> Souls {
+        match self {
+            CostCurve::Exponential(factor) => cost.times_pow(factor, i as f64),
+            CostCurve::Polynomial(power) => cost.times_pow((i + 1) as f64, power),
+            CostCurve::Stepped { every, factor } => {
+                cost.times_pow(factor, i.div_euclid(every) as f64)
+            }
+            CostCurve::Flat => cost,
+        }
+    }
+
+    pub fn ith(self, cost: Souls, i: i64) -> Souls {
+        self.price(cost, i).trunc()
+    }
+
+    /// What units `from` up to `from + n` (excluded) cost together.
+    pub fn sum(self, cost: Souls, from: i64, n: i64) -> Souls {
+        if n <= 0 {
+            return Souls::ZERO;
+        }
+
+        let end = from.saturating_add(n);
+        let mut total = Souls::ZERO;
+        let mut i = from;
+        let mut terms = 0;
+        while i < end && total < EXACT_TOTAL && terms < MAX_EXACT_TERMS {
+            let run = self.run(i, end);
+            total = total.saturating_add(self.ith(cost, i).saturating_times(run));
+            i += run;
+            terms += 1;
+        }
+        if i == end {
+            return total;
+        }
+        total
+            .saturating_add(self.estimate(cost, i, end - i))
+            .trunc()
+    }
+
+    /// How many units from `i` on, up to `end` (excluded), cost the same
+    /// as unit `i`.
+    fn run(self, i: i64, end: i64) -> i64 {
+        match self {
+            CostCurve::Exponential(1.0) | CostCurve::Polynomial(0.0) => end - i,
+            CostCurve::Stepped { every, .. } => {
+                let next = (i.div_euclid(every) + 1).saturating_mul(every);
+                cmp::min(next, end) - i
+            }
+            CostCurve::Flat => end - i,
+            _ => 1,
+        }
+    }
+
+    /// `sum` in closed form, fractions included.
+    fn estimate(self, cost: Souls, from: i64, n: i64) -> Souls {
+        match self {
+            CostCurve::Exponential(factor) => geometric(self.price(cost, from), factor, n),
+            CostCurve::Polynomial(power) => {
+                // Euler-Maclaurin: the integral of x^power over [a, b],
+                // plus half of both ends, plus a twelfth of how much the
+                // slope changed in between. Exact up to cubes.
+                let a = (from + 1) as f64;
+                let b = from.saturating_add(n) as f64;
+                let integral = cost
+                    .times_pow(b, power + 1.0)
+                    .saturating_sub(cost.times_pow(a, power + 1.0))
+                    .saturating_scale(1.0 / (power + 1.0));
+                let ends = cost
+                    .times_pow(a, power)
+                    .saturating_add(cost.times_pow(b, power))
+                    .saturating_scale(0.5);
+                let slopes = cost
+                    .times_pow(b, power - 1.0)
+                    .saturating_sub(cost.times_pow(a, power - 1.0))
+                    .saturating_scale(power / 12.0);
+                integral.saturating_add(ends).saturating_add(slopes)
+            }
+            CostCurve::Stepped { every, factor } => {
+                let end = from.saturating_add(n);
+                let (first, last) = (from.div_euclid(every), end.div_euclid(every));
+                let step = |k: i64| cost.times_pow(factor, k as f64);
+                if first == last {
+                    step(first).saturating_times(n)
+                } else {
+                    // what's left of the current step, the steps in
+                    // between, then the start of the last one
+                    let head = step(first).saturating_times(every * (first + 1) - from);
+                    let middle = geometric(step(first + 1), factor, last - first - 1)
+                        .saturating_times(every);
+                    let tail = step(last).saturating_times(end - every * last);
+                    head.saturating_add(middle).saturating_add(tail)
+                }
+            }
+            CostCurve::Flat => cost.saturating_times(n),
+        }
+    }
+
+    /// How many units, starting at `from`, `budget` pays for. The inverse
+    /// of `sum`.
+    pub fn affordable(self, cost: Souls, from: i64, budget: Souls) -> i64 {
+        let first = self.price(cost, from);
+        if budget <= Souls::ZERO || first <= Souls::ZERO {
+            return 0;
+        }
+
+        let guess = match self {
+            CostCurve::Exponential(factor) if factor > 1.0 => {
+                // budget >= first * (r^n - 1) / (r - 1), solved for n
+                let x = budget.log10() - first.log10() + (factor - 1.0).log10();
+                if x < 15.0 {
+                    (1.0 + 10f64.powf(x)).log10() / factor.log10()
+                } else {
+                    x / factor.log10()
+                }
+            }
+            CostCurve::Exponential(_) | CostCurve::Flat => budget.ratio(first),
+            _ => 1.0,
+        };
+        let guess = guess.floor().clamp(0.0, MAX_UNITS as f64) as i64;
+        self.search(cost, from, budget, guess)
+    }
+
+    /// `affordable`, starting from a `guess` that ignores rounding:
+    /// gallops away from it until the answer is bracketed, then bisects.
+    fn search(self, cost: Souls, from: i64, budget: Souls, guess: i64) -> i64 {
+        let fits = |n: i64| self.sum(cost, from, n) <= budget;
+
+        let (mut low, mut high) = (guess, guess);
+        let mut step = 1;
+        if fits(guess) {
+            loop {
+                high = cmp::min(MAX_UNITS, low.saturating_add(step));
+                if !fits(high) {
+                    break;
+                }
+                if high == MAX_UNITS {
+                    return high;
+                }
+                low = high;
+                step *= 2;
+            }
+        } else {
+            // fits(0) holds, budget is positive
+            loop {
+                low = cmp::max(0, high - step);
+                if fits(low) {
+                    break;
+                }
+                high = low;
+                step *= 2;
+            }
+        }
+
+        // fits(low) always holds, fits(high) never does
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if fits(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
+
+/// `first + first * r + ... + first * r^(n - 1)`
+fn geometric(first: Souls, r: f64, n: i64) -> Souls {
+    if n <= 0 {
+        return Souls::ZERO;
+    }
+    if r == 1.0 {
+        return first.saturating_times(n);
+    }
+
+    let growth = r.powf(n as f64);
+    if growth < 1e15 {
+        first.saturating_scale((growth - 1.0) / (r - 1.0))
+    } else {
+        // the -1 doesn't matter anymore
+        first
+            .times_pow(r, n as f64)
+            .saturating_scale(1.0 / (r - 1.0))
+    }
+}
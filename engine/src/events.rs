@@ -0,0 +1,225 @@
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use super::items::ItemSpec;
+use super::units::*;
+use super::upgrades::UpgradeSpec;
+use super::{CustomerKind, GameState, OfflineReport};
+
+/// Something that happens to the player, from `content/events.ron`.
+#[derive(Debug)]
+pub struct EventSpec {
+    /// Never rename, see the `content` module.
+    pub key: String,
+    pub name: String,
+    pub desc: String,
+    /// The event fires once all of these hold, see
+    /// `GameState::schedule_events`. Events without any are only ever
+    /// fired by the engine itself.
+    pub triggers: Vec<Trigger>,
+    /// What the player can answer. Without any, there's just "Okay".
+    pub choices: Vec<Choice>,
+    /// Informational only: shown as a notification that doesn't pause the
+    /// game, rather than a modal.
+    pub toast: bool,
+    /// Chance of firing each month, while the triggers hold. Random events
+    /// can fire any number of times, but at most one a month: chances add
+    /// up rather than stack, so keep their sum under 1.
+    pub chance: f64,
+    /// Applied whenever the event fires.
+    pub effects: Vec<Effect>,
+}
+
+#[derive(Debug)]
+pub struct Choice {
+    /// Stable identifier, used by save files and `Trigger::Chose`.
+    pub key: String,
+    pub label: String,
+    pub effects: Vec<Effect>,
+}
+
+/// What picking a choice does.
+#[derive(Debug)]
+pub enum Effect {
+    /// Souls gained, or paid if negative.
+    Souls(Souls),
+    /// Added to `goodness`, which then drifts back towards its target.
+    Goodness(f64),
+    /// Added to `base_birth_rate`, for good.
+    BirthRate(f64),
+    RevealItem(&'static ItemSpec),
+    RevealUpgrade(&'static UpgradeSpec),
+    /// Fires another event.
+    Event(&'static EventSpec),
+    /// Fraction of the living that die on the spot.
+    Kill(f64),
+    /// Fraction of the living that get born on the spot.
+    Births(f64),
+    /// Added to the birth and death rate factors for a few months, like
+    /// `ItemSpec::br_mod` and `ItemSpec::dr_mod`.
+    Rates {
+        birth: f64,
+        death: f64,
+        months: i64,
+    },
+}
+
+impl Choice {
+    /// Souls it takes to pick this: everything its `Effect::Souls` pay,
+    /// not counting what the others give back.
+    pub fn cost(&self) -> Souls {
+        let mut total = Souls::ZERO;
+        for effect in &self.effects {
+            if let Effect::Souls(q) = *effect {
+                if q.is_negative() {
+                    total = total.saturating_sub(q);
+                }
+            }
+        }
+        total
+    }
+}
+
+/// A condition on the game state.
+#[derive(Debug)]
+pub enum Trigger {
+    /// Owning at least `quantity` of `item`.
+    ItemQuantity {
+        item: &'static ItemSpec,
+        quantity: i64,
+    },
+    /// Holding at least this many souls.
+    Souls(Souls),
+    /// Reaching this month.
+    Month(i64),
+    /// Fewer humans alive than this.
+    PopulationBelow(Souls),
+    UpgradeBought(&'static UpgradeSpec),
+    /// Standing with `realm` of at least `min`.
+    Favor {
+        realm: CustomerKind,
+        min: Souls,
+    },
+    /// `choice` was picked the last time `event` fired.
+    Chose {
+        event: &'static EventSpec,
+        choice: String,
+    },
+}
+
+impl Trigger {
+    pub fn holds(&self, state: &GameState) -> bool {
+        match *self {
+            Trigger::ItemQuantity { item, quantity } => state.item_quantity(item) >= quantity,
+            Trigger::Souls(min) => state.souls() >= min,
+            Trigger::Month(month) => state.month >= month,
+            Trigger::PopulationBelow(max) => state.alive < max,
+            Trigger::UpgradeBought(upgrade) => state.upgrade_bought(upgrade),
+            Trigger::Favor { realm, min } => state.customer(realm).favor >= min,
+            Trigger::Chose { event, ref choice } => state
+                .events
+                .get(event)
+                .and_then(|ev| ev.chosen)
+                .map(|chosen| &chosen.key == choice)
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub spec: &'static EventSpec,
+    /// Month it (last) fired.
+    pub month: i64,
+    /// Acknowledged, or dismissed for toasts: the player has read it.
+    pub consumed: bool,
+    /// What the engine had to say when it fired, shown after `desc`.
+    pub details: Option<Details>,
+    /// What the player answered, for events with choices.
+    pub chosen: Option<&'static Choice>,
+}
+
+/// One firing of an event, as listed in the inbox.
+#[derive(Debug, Clone)]
+pub struct InboxEntry {
+    /// Counts up from the first entry, and stays put when older ones are
+    /// dropped, unlike the index.
+    pub id: u64,
+    pub spec: &'static EventSpec,
+    pub month: i64,
+    pub details: Option<Details>,
+    pub chosen: Option<&'static Choice>,
+    pub read: bool,
+}
+
+/// Specifics of a builtin event. Numbers are kept as such, so frontends
+/// can write them in the player's `NumberFormat`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Details {
+    /// For `away_report`.
+    Away(OfflineReport),
+    /// For `overflow`: what the game was busy doing at the time.
+    Overflow { action: String },
+}
+
+impl Hash for EventSpec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state)
+    }
+}
+
+impl std::cmp::PartialEq for EventSpec {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.key == rhs.key
+    }
+}
+
+impl std::cmp::Eq for EventSpec {}
+
+impl Default for EventSpec {
+    fn default() -> Self {
+        Self {
+            name: "<untitled>".to_owned(),
+            desc: String::new(),
+            key: String::new(),
+            triggers: vec![],
+            choices: vec![],
+            toast: false,
+            chance: 0.0,
+            effects: vec![],
+        }
+    }
+}
+
+impl EventSpec {
+    pub fn instantiate(&'static self) -> Event {
+        Event {
+            spec: self,
+            month: 0,
+            consumed: false,
+            details: None,
+            chosen: None,
+        }
+    }
+
+    pub fn is_random(&self) -> bool {
+        self.chance > 0.0
+    }
+
+    /// Whether the scheduler should fire this event now.
+    pub fn triggered(&self, state: &GameState) -> bool {
+        !self.is_random()
+            && !self.triggers.is_empty()
+            && self.triggers.iter().all(|t| t.holds(state))
+    }
+
+    /// Whether this random event is in the running this month: its
+    /// triggers hold, and it isn't a modal still waiting to be read.
+    /// Toasts don't hold anything up, so they can pile up unread.
+    pub fn may_roll(&self, state: &GameState) -> bool {
+        self.is_random()
+            && (self.toast || state.events.get(self).map(|ev| ev.consumed).unwrap_or(true))
+            && self.triggers.iter().all(|t| t.holds(state))
+    }
+}
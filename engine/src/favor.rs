@@ -0,0 +1,66 @@
+//! Standing with Heaven and Hell.
+//!
+//! Every soul credited to a realm (accountants included) becomes favor
+//! with that realm. Some items won't even be offered until you're in
+//! good enough standing, see `ItemSpec::min_heaven_favor` and
+//! `ItemSpec::min_hell_favor`.
+//!
+//! Favor can also be spent: should humanity die out, Heaven will start it
+//! over for `REPOPULATION_FAVOR`.
+
+use super::units::*;
+
+pub struct FavorTier {
+    pub name: &'static str,
+    pub min: Souls,
+}
+
+/// Heaven favor it takes to repopulate the Earth after extinction.
+pub const REPOPULATION_FAVOR: Souls = Souls::new(10_000);
+
+/// How many humans Heaven puts back on Earth.
+pub const REPOPULATION: Souls = Souls::new(10_000);
+
+/// Sorted by `min`, the first tier is where everyone starts.
+pub const TIERS: &[FavorTier] = &[
+    FavorTier {
+        name: "Unknown",
+        min: Souls::new(0),
+    },
+    FavorTier {
+        name: "Noticed",
+        min: Souls::new(1_000),
+    },
+    FavorTier {
+        name: "Trusted",
+        min: Souls::new(10_000),
+    },
+    FavorTier {
+        name: "Favored",
+        min: Souls::new(100_000),
+    },
+    FavorTier {
+        name: "Honored",
+        min: Souls::new(1_000_000),
+    },
+    FavorTier {
+        name: "Exalted",
+        min: Souls::new(10_000_000),
+    },
+];
+
+/// Index into `TIERS` of the tier `favor` reaches.
+pub fn tier_index(favor: Souls) -> usize {
+    TIERS
+        .iter()
+        .rposition(|tier| favor >= tier.min)
+        .unwrap_or(0)
+}
+
+pub fn tier(favor: Souls) -> &'static FavorTier {
+    &TIERS[tier_index(favor)]
+}
+
+pub fn next_tier(favor: Souls) -> Option<&'static FavorTier> {
+    TIERS.get(tier_index(favor) + 1)
+}
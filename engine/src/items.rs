@@ -0,0 +1,240 @@
+use super::cost::CostCurve;
+use super::units::*;
+use super::upgrades::UpgradeEffect;
+use super::{CustomerKind, GameState};
+use serde::Deserialize;
+use std::cmp;
+use std::hash::{Hash, Hasher};
+
+/// Fraction of the price given back when selling, unless the item says
+/// otherwise.
+pub const REFUND_RATIO: f64 = 0.5;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+pub enum ItemCategory {
+    Harvest,
+    Finance,
+    Initiatives,
+    Events,
+    Upgrades,
+}
+
+/// Something the shop sells, from `content/items.ron`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ItemSpec {
+    /// Never rename, see the `content` module.
+    pub key: String,
+    pub category: ItemCategory,
+    pub name: String,
+    pub desc: String,
+    pub cost: Souls,
+    /// How `cost` goes up with each unit bought.
+    pub curve: CostCurve,
+    /// Fraction of the price given back when selling, `REFUND_RATIO` if
+    /// unset.
+    pub refund: Option<f64>,
+    pub spc: Option<Souls>,
+    pub spt: Option<Souls>,
+    pub br_mod: Option<f64>,
+    pub dr_mod: Option<f64>,
+    pub remit_mod: Option<f64>,
+    pub interest: Option<f64>,
+    /// Nudges where the population's virtue is headed, per unit owned.
+    pub goodness_mod: Option<f64>,
+
+    pub min_hell_favor: Option<Souls>,
+    pub min_heaven_favor: Option<Souls>,
+
+    // Unique effects
+    pub unique: bool,
+    pub pop_multiplier: Option<f64>,
+    pub pop_kill_ratio: Option<f64>,
+}
+
+pub struct Stats {
+    pub base: Souls,
+    pub effective: Souls,
+    pub bonus: f64,
+}
+
+impl Stats {
+    pub fn multiply(&self, quantity: i64) -> Souls {
+        self.effective.saturating_times(quantity)
+    }
+}
+
+impl ItemSpec {
+    fn bonus(&self, state: &GameState, f: fn(effect: &UpgradeEffect) -> Option<f64>) -> f64 {
+        let mut bonus = 1.0f64;
+        if let Some(effects) = state.effects.get(self) {
+            for effect in effects {
+                if let Some(modo) = f(effect) {
+                    bonus += modo;
+                }
+            }
+        }
+        bonus
+    }
+
+    fn effective(
+        &self,
+        state: &GameState,
+        qt: Option<Souls>,
+        f: fn(effect: &UpgradeEffect) -> Option<f64>,
+    ) -> Option<Stats> {
+        if let Some(base) = qt {
+            let bonus = self.bonus(state, f);
+            let effective = base.saturating_scale(bonus).trunc();
+            Some(Stats {
+                base,
+                effective,
+                bonus,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_spc(&self, state: &GameState) -> Option<Stats> {
+        self.effective(state, self.spc, |fx| fx.spc_mod)
+    }
+
+    pub fn get_spt(&self, state: &GameState) -> Option<Stats> {
+        self.effective(state, self.spt, |fx| fx.spt_mod)
+    }
+
+    pub fn min_favor(&self, kind: CustomerKind) -> Option<Souls> {
+        match kind {
+            CustomerKind::Heaven => self.min_heaven_favor,
+            CustomerKind::Hell => self.min_hell_favor,
+        }
+    }
+
+    /// Whether both realms think highly enough of us to offer this item.
+    pub fn favor_allows(&self, state: &GameState) -> bool {
+        [CustomerKind::Heaven, CustomerKind::Hell]
+            .iter()
+            .all(|&kind| match self.min_favor(kind) {
+                Some(min) => state.customer(kind).favor >= min,
+                None => true,
+            })
+    }
+
+    /// Monthly interest rate paid by a single unit, upgrades included.
+    pub fn get_interest(&self, state: &GameState) -> Option<f64> {
+        self.interest
+            .map(|base| base * self.bonus(state, |fx| fx.interest_mod))
+    }
+}
+
+impl Hash for ItemSpec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state)
+    }
+}
+
+impl std::cmp::PartialEq for ItemSpec {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.key == rhs.key
+    }
+}
+
+impl std::cmp::Eq for ItemSpec {}
+
+impl Default for ItemSpec {
+    fn default() -> Self {
+        Self {
+            key: String::new(),
+            category: ItemCategory::Harvest,
+            name: "<missing>".to_owned(),
+            desc: String::new(),
+            cost: Souls::new(1),
+            curve: CostCurve::default(),
+            refund: None,
+            spc: None,
+            spt: None,
+            br_mod: None,
+            dr_mod: None,
+
+            pop_multiplier: None,
+            pop_kill_ratio: None,
+            unique: false,
+
+            min_heaven_favor: None,
+            min_hell_favor: None,
+
+            interest: None,
+            remit_mod: None,
+            goodness_mod: None,
+        }
+    }
+}
+
+impl ItemSpec {
+    pub fn instantiate(&'static self, quantity: i64) -> Item {
+        Item {
+            spec: self,
+            quantity,
+            revealed: quantity > 0,
+        }
+    }
+
+    pub fn ith_cost(&'static self, i: i64) -> Souls {
+        self.curve.ith(self.cost, i)
+    }
+
+    pub fn refund_ratio(&self) -> f64 {
+        self.refund.unwrap_or(REFUND_RATIO).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub spec: &'static ItemSpec,
+    pub quantity: i64,
+    pub revealed: bool,
+}
+
+impl Item {
+    pub fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    /// Unique items can only be bought once, after which they're done.
+    pub fn is_done(&self) -> bool {
+        self.spec.unique && self.quantity > 0
+    }
+
+    pub fn cost(&self) -> Souls {
+        self.spec.ith_cost(self.quantity)
+    }
+
+    /// What the next `n` units cost together.
+    pub fn cost_n(&self, n: i64) -> Souls {
+        self.spec.curve.sum(self.spec.cost, self.quantity, n)
+    }
+
+    /// How many more units `budget` pays for, the inverse of `cost_n`.
+    pub fn affordable(&self, budget: Souls) -> i64 {
+        self.spec
+            .curve
+            .affordable(self.spec.cost, self.quantity, budget)
+    }
+
+    /// Whether there's anything to sell back. Unique items did their thing
+    /// already and can't be undone.
+    pub fn can_sell(&self) -> bool {
+        !self.spec.unique && self.quantity > 0
+    }
+
+    /// What selling the last `n` units gives back.
+    pub fn refund_n(&self, n: i64) -> Souls {
+        let n = cmp::min(n, self.quantity);
+        self.spec
+            .curve
+            .sum(self.spec.cost, self.quantity - n, n)
+            .saturating_scale(self.spec.refund_ratio())
+            .trunc()
+    }
+}
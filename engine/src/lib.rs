@@ -0,0 +1,23 @@
+//! Headless simulation for Death Inc.
+//!
+//! Everything in here is plain Rust: no stdweb, no yew. The web frontend
+//! owns a `GameState`, feeds it `Msg`s and renders whatever it looks like
+//! afterwards.
+
+pub mod content;
+pub mod cost;
+pub mod events;
+pub mod favor;
+pub mod items;
+pub mod rng;
+pub mod save;
+pub mod state;
+pub mod units;
+pub mod upgrades;
+
+pub use state::{Customer, CustomerKind, GameState, Modifier, Msg, OfflineReport, Transaction};
+
+// ok, ok, I get it
+pub const DAYS_PER_YEAR: f64 = 365.25;
+pub const DAYS_PER_TICK: f64 = 31.0;
+pub const TICK_UNIT: &str = "month";
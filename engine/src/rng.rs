@@ -0,0 +1,42 @@
+//! Seeded randomness.
+//!
+//! The generator lives in `GameState` and is saved along with it, so a
+//! game loaded from a save rolls the same dice it would have rolled had it
+//! never been closed. It's a PCG32 (see <https://www.pcg-random.org>):
+//! tiny, fast, and good enough for deciding when the plague hits.
+
+use serde::{Deserialize, Serialize};
+
+const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+/// Picks the stream, any odd number will do.
+const INCREMENT: u64 = 1_442_695_040_888_963_407;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        let bits = (u64::from(self.next_u32()) << 21) ^ u64::from(self.next_u32() >> 11);
+        bits as f64 / (1u64 << 53) as f64
+    }
+}
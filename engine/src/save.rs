@@ -0,0 +1,561 @@
+//! Save files.
+//!
+//! Specs are referenced by their stable `key`. Anything the save doesn't
+//! mention (say, an item added in a later release) keeps its default from
+//! `GameState::new`, and anything the game doesn't know about anymore is
+//! dropped on load.
+
+use std::cmp;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD};
+use base64::engine::DecodePaddingMode;
+use base64::{alphabet, DecodeError, Engine};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::content::registry;
+use super::events::{Details, InboxEntry};
+use super::rng::Rng;
+use super::units::*;
+use super::{Customer, GameState, Modifier, Transaction};
+
+/// Bump this whenever the shape of `SaveData` changes, and add a
+/// migration to `MIGRATIONS`.
+pub const SAVE_VERSION: u32 = 12;
+
+/// `MIGRATIONS[n]` turns a version `n + 1` save into a version `n + 2` one.
+const MIGRATIONS: &[fn(&mut Value)] = &[
+    // v2: saves remember when they were made, for offline progress
+    |v| v["saved_at"] = Value::Null,
+    // v3: customers keep a log of recent remittances
+    |v| {
+        v["heaven"]["transactions"] = Value::Array(vec![]);
+        v["hell"]["transactions"] = Value::Array(vec![]);
+    },
+    // v4: favor, which everything remitted so far counts towards
+    |v| {
+        for realm in &["heaven", "hell"] {
+            v[realm]["favor"] = v[realm]["given"].clone();
+        }
+    },
+    // v5: souls are split into virtuous and sinful pools, by goodness
+    |v| {
+        let souls = v["souls"].as_i64().unwrap_or(0);
+        let goodness = v["goodness"].as_f64().unwrap_or(1.0);
+        let virtuous = (souls as f64 * goodness).round() as i64;
+        v["virtuous"] = virtuous.into();
+        v["sinful"] = (souls - virtuous).into();
+    },
+    // v6: amounts past 10^15 are stored as [mantissa, exponent], plain
+    // integers still read fine
+    |_| {},
+    // v7: events remember the choice that was picked, none so far
+    |_| {},
+    // v8: events remember the month they arrived, older ones are from the
+    // very start
+    |v| {
+        if let Some(events) = v["events"].as_array_mut() {
+            for ev in events {
+                ev["month"] = 0.into();
+            }
+        }
+    },
+    // v9: randomness, seeded from when the save was made so old games
+    // don't all roll the same
+    |v| {
+        let seed = v["saved_at"].as_i64().unwrap_or(0) as u64;
+        v["rng"] = serde_json::to_value(Rng::new(seed)).expect("rng should always serialize");
+        v["modifiers"] = Value::Array(vec![]);
+    },
+    // v10: event details are data rather than text, and there's no
+    // getting the numbers back out of the text
+    |v| {
+        if let Some(events) = v["events"].as_array_mut() {
+            for ev in events {
+                ev["details"] = Value::Null;
+            }
+        }
+    },
+    // v11: the inbox lists every time an event fired, the best we can do
+    // for older saves is the last time
+    |v| {
+        let inbox = v["events"]
+            .as_array()
+            .map(|events| {
+                events
+                    .iter()
+                    .map(|ev| {
+                        serde_json::json!({
+                            "key": ev["key"],
+                            "month": ev["month"],
+                            "details": ev["details"],
+                            "choice": ev["choice"],
+                            "read": ev["consumed"],
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        v["inbox"] = Value::Array(inbox);
+    },
+    // v12: inbox entries are numbered, so they can be told apart once the
+    // oldest are dropped
+    |v| {
+        if let Some(inbox) = v["inbox"].as_array_mut() {
+            for (id, entry) in inbox.iter_mut().enumerate() {
+                entry["id"] = id.into();
+            }
+        }
+    },
+];
+
+/// Exported saves start with this, so we can tell them apart from
+/// random garbage pasted in the import field.
+const EXPORT_MAGIC: &[u8] = b"DInc";
+
+/// magic + format version (u32 LE) + crc32 of the JSON (u32 LE)
+const EXPORT_HEADER_LEN: usize = 12;
+
+/// Like `STANDARD`, but doesn't choke on missing padding, so truncated
+/// strings are reported as such rather than as invalid base64.
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true),
+);
+
+/// Specs that were renamed after shipping, as `(old key, new key)`.
+const RENAMED_KEYS: &[(&str, &str)] = &[];
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveData {
+    pub version: u32,
+    pub saved_at: Option<i64>,
+
+    pub month: i64,
+
+    pub alive: Souls,
+    pub due: Souls,
+    pub virtuous: Souls,
+    pub sinful: Souls,
+
+    pub base_birth_rate: f64,
+    pub base_death_rate: f64,
+    pub goodness: f64,
+
+    pub heaven: CustomerSave,
+    pub hell: CustomerSave,
+
+    pub items: Vec<ItemSave>,
+    pub upgrades: Vec<UpgradeSave>,
+    pub events: Vec<EventSave>,
+    pub inbox: Vec<InboxSave>,
+    pub modifiers: Vec<ModifierSave>,
+
+    pub rng: Rng,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CustomerSave {
+    pub given: Souls,
+    pub favor: Souls,
+    pub transactions: Vec<TransactionSave>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransactionSave {
+    pub month: i64,
+    pub remitted: Souls,
+    pub credited: Souls,
+}
+
+impl CustomerSave {
+    fn new(customer: &Customer) -> Self {
+        Self {
+            given: customer.given,
+            favor: customer.favor,
+            transactions: customer
+                .transactions
+                .iter()
+                .map(|tx| TransactionSave {
+                    month: tx.month,
+                    remitted: tx.remitted,
+                    credited: tx.credited,
+                })
+                .collect(),
+        }
+    }
+
+    fn restore(self, customer: &mut Customer) {
+        customer.given = self.given;
+        customer.favor = self.favor;
+        customer.transactions = self
+            .transactions
+            .into_iter()
+            .map(|tx| Transaction {
+                month: tx.month,
+                remitted: tx.remitted,
+                credited: tx.credited,
+            })
+            .collect();
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ItemSave {
+    pub key: String,
+    pub quantity: i64,
+    pub revealed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpgradeSave {
+    pub key: String,
+    pub revealed: bool,
+    pub bought: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ModifierSave {
+    pub birth: f64,
+    pub death: f64,
+    pub until: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EventSave {
+    pub key: String,
+    pub month: i64,
+    pub consumed: bool,
+    #[serde(default)]
+    pub details: Option<Details>,
+    #[serde(default)]
+    pub choice: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InboxSave {
+    pub id: u64,
+    pub key: String,
+    pub month: i64,
+    #[serde(default)]
+    pub details: Option<Details>,
+    #[serde(default)]
+    pub choice: Option<String>,
+    pub read: bool,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Malformed(String),
+    NewerVersion { found: u32, supported: u32 },
+    Corrupt(String),
+    Truncated,
+    Empty,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Malformed(reason) => write!(f, "malformed save: {}", reason),
+            LoadError::NewerVersion { found, supported } => write!(
+                f,
+                "save is from a newer version of the game (format {}, we only know up to {})",
+                found, supported
+            ),
+            LoadError::Corrupt(reason) => write!(f, "corrupt save: {}", reason),
+            LoadError::Truncated => write!(f, "save is truncated, was it copied in full?"),
+            LoadError::Empty => write!(f, "nothing to import, paste an exported save first"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(e: serde_json::Error) -> Self {
+        LoadError::Malformed(e.to_string())
+    }
+}
+
+pub fn to_json(state: &GameState) -> String {
+    serde_json::to_string(&state.to_save()).expect("save data should always serialize")
+}
+
+pub fn from_json(input: &str) -> Result<GameState, LoadError> {
+    let mut value: Value = serde_json::from_str(input)?;
+    migrate(&mut value)?;
+    let save: SaveData = serde_json::from_value(value)?;
+    Ok(GameState::from_save(save))
+}
+
+/// Produces a compressed, checksummed, base64-encoded save that can be
+/// pasted around (bug reports, chat, etc.)
+pub fn export(state: &GameState) -> String {
+    let json = to_json(state);
+
+    let mut data = Vec::with_capacity(EXPORT_HEADER_LEN + json.len() / 4);
+    data.extend_from_slice(EXPORT_MAGIC);
+    data.extend_from_slice(&SAVE_VERSION.to_le_bytes());
+    data.extend_from_slice(&crc32fast::hash(json.as_bytes()).to_le_bytes());
+
+    let mut encoder = DeflateEncoder::new(data, Compression::best());
+    encoder
+        .write_all(json.as_bytes())
+        .expect("writing to a Vec cannot fail");
+    let data = encoder.finish().expect("writing to a Vec cannot fail");
+
+    STANDARD.encode(&data)
+}
+
+/// Reverse of `export`.
+pub fn import(input: &str) -> Result<GameState, LoadError> {
+    let input: String = input.split_whitespace().collect();
+    if input.is_empty() {
+        return Err(LoadError::Empty);
+    }
+    let data = LENIENT_BASE64
+        .decode(input.as_bytes())
+        .map_err(|e| match e {
+            DecodeError::InvalidLength(_) => LoadError::Truncated,
+            _ => LoadError::Corrupt("not a valid export string".to_owned()),
+        })?;
+
+    if !data.starts_with(&EXPORT_MAGIC[..data.len().min(EXPORT_MAGIC.len())]) {
+        return Err(LoadError::Corrupt("not a Death Inc. save".to_owned()));
+    }
+    if data.len() < EXPORT_HEADER_LEN {
+        return Err(LoadError::Truncated);
+    }
+
+    let version = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+    if version > SAVE_VERSION {
+        return Err(LoadError::NewerVersion {
+            found: version,
+            supported: SAVE_VERSION,
+        });
+    }
+    let checksum = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
+
+    let mut json = String::new();
+    DeflateDecoder::new(&data[EXPORT_HEADER_LEN..])
+        .read_to_string(&mut json)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => LoadError::Truncated,
+            _ => LoadError::Corrupt(e.to_string()),
+        })?;
+
+    if crc32fast::hash(json.as_bytes()) != checksum {
+        return Err(LoadError::Corrupt("checksum mismatch".to_owned()));
+    }
+
+    from_json(&json)
+}
+
+/// Brings a save of any known version up to `SAVE_VERSION`.
+fn migrate(value: &mut Value) -> Result<(), LoadError> {
+    let mut version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| LoadError::Malformed("missing version".to_owned()))?
+        as u32;
+
+    if version > SAVE_VERSION {
+        return Err(LoadError::NewerVersion {
+            found: version,
+            supported: SAVE_VERSION,
+        });
+    }
+    if version == 0 {
+        return Err(LoadError::Malformed("invalid version 0".to_owned()));
+    }
+
+    while version < SAVE_VERSION {
+        MIGRATIONS[(version - 1) as usize](value);
+        version += 1;
+        value["version"] = version.into();
+    }
+    Ok(())
+}
+
+fn current_key(key: &str) -> &str {
+    RENAMED_KEYS
+        .iter()
+        .find(|(old, _)| *old == key)
+        .map(|(_, new)| *new)
+        .unwrap_or(key)
+}
+
+impl GameState {
+    pub fn to_save(&self) -> SaveData {
+        SaveData {
+            version: SAVE_VERSION,
+            saved_at: self.saved_at,
+
+            month: self.month,
+
+            alive: self.alive,
+            due: self.due,
+            virtuous: self.virtuous,
+            sinful: self.sinful,
+
+            base_birth_rate: self.base_birth_rate,
+            base_death_rate: self.base_death_rate,
+            goodness: self.goodness,
+
+            heaven: CustomerSave::new(&self.heaven),
+            hell: CustomerSave::new(&self.hell),
+
+            items: self
+                .items
+                .values()
+                .map(|item| ItemSave {
+                    key: item.spec.key.clone(),
+                    quantity: item.quantity,
+                    revealed: item.revealed,
+                })
+                .collect(),
+            upgrades: self
+                .upgrades
+                .values()
+                .map(|up| UpgradeSave {
+                    key: up.spec.key.clone(),
+                    revealed: up.revealed,
+                    bought: up.bought,
+                })
+                .collect(),
+            events: self
+                .events
+                .values()
+                .map(|ev| EventSave {
+                    key: ev.spec.key.clone(),
+                    month: ev.month,
+                    consumed: ev.consumed,
+                    details: ev.details.clone(),
+                    choice: ev.chosen.map(|c| c.key.clone()),
+                })
+                .collect(),
+            inbox: self
+                .inbox
+                .iter()
+                .map(|entry| InboxSave {
+                    id: entry.id,
+                    key: entry.spec.key.clone(),
+                    month: entry.month,
+                    details: entry.details.clone(),
+                    choice: entry.chosen.map(|c| c.key.clone()),
+                    read: entry.read,
+                })
+                .collect(),
+            modifiers: self
+                .modifiers
+                .iter()
+                .map(|m| ModifierSave {
+                    birth: m.birth,
+                    death: m.death,
+                    until: m.until,
+                })
+                .collect(),
+
+            rng: self.rng.clone(),
+        }
+    }
+
+    pub fn from_save(save: SaveData) -> Self {
+        let mut s = Self::new();
+
+        s.saved_at = save.saved_at;
+        s.month = save.month;
+
+        s.alive = save.alive;
+        s.due = save.due;
+        s.virtuous = save.virtuous;
+        s.sinful = save.sinful;
+
+        s.base_birth_rate = save.base_birth_rate;
+        s.base_death_rate = save.base_death_rate;
+        s.goodness = save.goodness;
+
+        save.heaven.restore(&mut s.heaven);
+        save.hell.restore(&mut s.hell);
+
+        for saved in save.items {
+            let key = current_key(&saved.key);
+            if let Some(item) = s.items.values_mut().find(|item| item.spec.key == key) {
+                item.quantity = saved.quantity;
+                if item.spec.unique {
+                    // older versions let you buy these more than once
+                    item.quantity = cmp::min(item.quantity, 1);
+                }
+                item.revealed = saved.revealed;
+            }
+        }
+
+        let mut bought = Vec::new();
+        for saved in save.upgrades {
+            let key = current_key(&saved.key);
+            if let Some(up) = s.upgrades.values_mut().find(|up| up.spec.key == key) {
+                up.revealed = saved.revealed;
+                if saved.bought {
+                    up.bought = true;
+                    bought.push(up.spec);
+                }
+            }
+        }
+        for spec in bought {
+            s.apply_upgrade(spec);
+        }
+
+        for saved in save.events {
+            if let Some(spec) = registry().event(current_key(&saved.key)) {
+                let mut ev = spec.instantiate();
+                ev.month = saved.month;
+                ev.consumed = saved.consumed;
+                ev.details = saved.details;
+                ev.chosen = saved
+                    .choice
+                    .and_then(|key| spec.choices.iter().find(|c| c.key == key));
+                s.events.insert(spec, ev);
+            }
+        }
+
+        s.inbox = save
+            .inbox
+            .into_iter()
+            .filter_map(|saved| {
+                let spec = registry().event(current_key(&saved.key))?;
+                Some(InboxEntry {
+                    id: saved.id,
+                    spec,
+                    month: saved.month,
+                    details: saved.details,
+                    chosen: saved
+                        .choice
+                        .and_then(|key| spec.choices.iter().find(|c| c.key == key)),
+                    read: saved.read,
+                })
+            })
+            .collect();
+        // saves from before the cap kept every letter
+        s.trim_inbox();
+
+        s.modifiers = save
+            .modifiers
+            .into_iter()
+            .map(|m| Modifier {
+                birth: m.birth,
+                death: m.death,
+                until: m.until,
+            })
+            .collect();
+        s.rng = save.rng;
+
+        s
+    }
+}
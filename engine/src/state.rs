@@ -0,0 +1,1026 @@
+use std::cmp;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use super::content::registry;
+use super::events::{Details, Effect, Event, EventSpec, InboxEntry};
+use super::favor;
+use super::items::{self, Item, ItemSpec};
+use super::rng::Rng;
+use super::units::*;
+use super::upgrades::{Upgrade, UpgradeEffect, UpgradeSpec};
+use super::{DAYS_PER_TICK, DAYS_PER_YEAR};
+
+/// How much of the gap between `goodness` and `target_goodness` closes
+/// every month.
+const GOODNESS_DRIFT: f64 = 0.02;
+
+/// How many remittances each customer remembers.
+const MAX_TRANSACTIONS: usize = 50;
+
+/// How many entries the inbox keeps, not counting the ones still waiting
+/// for the player.
+const MAX_INBOX: usize = 100;
+
+/// How many months `fast_forward` simulates between snapshots. Each
+/// snapshot clones the whole state, too much to do for every month of a
+/// long absence.
+const CATCH_UP_BATCH: i64 = 120;
+
+#[derive(Clone)]
+pub struct Customer {
+    pub kind: CustomerKind,
+    pub name: String,
+    pub sign: String,
+    pub given: Souls,
+    /// Standing with this realm, see the `favor` module.
+    pub favor: Souls,
+    /// Most recent remittances, oldest first.
+    pub transactions: Vec<Transaction>,
+}
+
+impl Customer {
+    fn record(&mut self, tx: Transaction) -> Option<()> {
+        self.given = self.given.checked_add(tx.credited)?;
+        self.favor = self.favor.checked_add(tx.credited)?;
+        self.transactions.push(tx);
+        if self.transactions.len() > MAX_TRANSACTIONS {
+            self.transactions.remove(0);
+        }
+        Some(())
+    }
+}
+
+/// Temporary change to the birth and death rates, see `Effect::Rates`.
+#[derive(Clone)]
+pub struct Modifier {
+    pub birth: f64,
+    pub death: f64,
+    /// Month it wears off.
+    pub until: i64,
+}
+
+#[derive(Clone)]
+pub struct Transaction {
+    pub month: i64,
+    /// Souls that left our pocket
+    pub remitted: Souls,
+    /// Souls the customer saw, accountants included
+    pub credited: Souls,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum CustomerKind {
+    Heaven,
+    Hell,
+}
+
+pub enum Msg {
+    Tick,
+    Remit {
+        quantity: Souls,
+        target: CustomerKind,
+    },
+    Harvest,
+    Purchase {
+        spec: &'static ItemSpec,
+        quantity: i64,
+    },
+    Sell {
+        spec: &'static ItemSpec,
+        quantity: i64,
+    },
+    ConsumeEvent {
+        spec: &'static EventSpec,
+    },
+    /// Opens an inbox entry, by `InboxEntry::id`. Opening a toast
+    /// dismisses it.
+    Read {
+        entry: u64,
+    },
+    /// Answers an event with one of its `choices`, by index.
+    Choose {
+        spec: &'static EventSpec,
+        choice: usize,
+    },
+    PurchaseUpgrade {
+        spec: &'static UpgradeSpec,
+    },
+    /// Spends Heaven favor to start humanity over after extinction.
+    Repopulate,
+}
+
+/// What happened during `GameState::fast_forward`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OfflineReport {
+    pub months: i64,
+    pub souls: Souls,
+    pub population: Souls,
+    pub corpses: Souls,
+}
+
+#[derive(Clone)]
+pub struct GameState {
+    /// Wall-clock time of the last save, in milliseconds since the UNIX
+    /// epoch. The engine has no clock of its own: frontends fill it in.
+    pub saved_at: Option<i64>,
+
+    pub alive: Souls,
+    pub due: Souls,
+
+    /// Harvested souls are split between these according to `goodness`.
+    /// Heaven only takes the former, Hell only the latter, and the shop
+    /// takes both.
+    pub virtuous: Souls,
+    pub sinful: Souls,
+
+    pub base_birth_rate: f64,
+    pub base_death_rate: f64,
+
+    /// Fraction of the population that is virtuous, between 0 and 1. It
+    /// drifts towards `target_goodness` over time.
+    pub goodness: f64,
+
+    pub month: i64,
+
+    pub heaven: Customer,
+    pub hell: Customer,
+
+    pub items: IndexMap<&'static ItemSpec, Item>,
+    pub effects: IndexMap<&'static ItemSpec, Vec<&'static UpgradeEffect>>,
+    pub upgrades: IndexMap<&'static UpgradeSpec, Upgrade>,
+    /// Every event fired so far, as of the last time it fired, in that
+    /// order. The ones not consumed yet are the queue of things to show.
+    pub events: IndexMap<&'static EventSpec, Event>,
+    /// Every time any event fired, oldest first. Only ever appended to.
+    pub inbox: Vec<InboxEntry>,
+    pub modifiers: Vec<Modifier>,
+
+    /// Saved with the game, so loading doesn't reroll anything.
+    pub rng: Rng,
+
+    pub cheat: bool,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState {
+    /// A new game with a fixed seed, see `with_seed`.
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// A new game. Two games with the same seed play out the same, given
+    /// the same inputs.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut s = Self {
+            saved_at: None,
+
+            due: Souls::ZERO,
+            virtuous: Souls::ZERO,
+            sinful: Souls::ZERO,
+
+            month: 0,
+
+            // 2019 stats:
+            // birth_rate: 18.5,
+            // death_rate: 7.8,
+            // alive: 7 * Souls::B,
+
+            // Better starting point:
+            base_birth_rate: 6.0,
+            base_death_rate: 4.0,
+            alive: 800 * Souls::K,
+
+            goodness: 1.0,
+
+            heaven: Customer {
+                kind: CustomerKind::Heaven,
+                name: "Heaven".to_owned(),
+                sign: "✝️".to_owned(),
+                given: Souls::ZERO,
+                favor: Souls::ZERO,
+                transactions: Vec::new(),
+            },
+            hell: Customer {
+                kind: CustomerKind::Hell,
+                name: "Hell".to_owned(),
+                sign: "⛧️".to_owned(),
+                given: Souls::ZERO,
+                favor: Souls::ZERO,
+                transactions: Vec::new(),
+            },
+
+            items: IndexMap::new(),
+            effects: IndexMap::new(),
+            events: IndexMap::new(),
+            inbox: Vec::new(),
+            upgrades: IndexMap::new(),
+            modifiers: Vec::new(),
+
+            rng: Rng::new(seed),
+
+            cheat: false,
+        };
+
+        let content = registry();
+
+        for &spec in &content.items {
+            s.add_item(spec, 0);
+        }
+        for &spec in &content.upgrades {
+            s.add_upgrade(spec);
+        }
+
+        // events
+        s.add_event(content.builtin_event("welcome"));
+
+        s
+    }
+
+    /// Applies a single message to the game state. Returns true if
+    /// anything changed, so frontends know whether to re-render.
+    pub fn apply(&mut self, msg: Msg) -> bool {
+        match msg {
+            Msg::Tick => self.tick(),
+            Msg::Harvest => self
+                .atomically("harvesting", |s| {
+                    s.harvest(s.souls_per_click())?;
+                    Some(true)
+                })
+                .unwrap_or(true),
+            Msg::Remit { quantity, target } => self
+                .atomically("remitting souls", |s| {
+                    let remitted = cmp::min(s.remittable(target), quantity);
+                    if remitted <= Souls::ZERO {
+                        return Some(false);
+                    }
+                    let tx = Transaction {
+                        month: s.month,
+                        remitted,
+                        credited: s.remit_credit(remitted),
+                    };
+                    match target {
+                        CustomerKind::Heaven => s.virtuous = s.virtuous.checked_sub(remitted)?,
+                        CustomerKind::Hell => s.sinful = s.sinful.checked_sub(remitted)?,
+                    }
+                    s.customer_mut(target).record(tx)?;
+                    Some(true)
+                })
+                .unwrap_or(true),
+            Msg::Purchase { quantity, spec } => {
+                if !spec.favor_allows(self) {
+                    return false;
+                }
+
+                // unique items are one-shot
+                let quantity = if spec.unique {
+                    cmp::min(quantity, 1 - self.item_quantity(spec))
+                } else {
+                    quantity
+                };
+                if quantity <= 0 {
+                    return false;
+                }
+
+                self.atomically("buying items", |s| {
+                    let item = &s.items[spec];
+                    let quantity = cmp::min(quantity, item.affordable(s.souls()));
+                    if quantity <= 0 {
+                        return Some(false);
+                    }
+                    s.spend(item.cost_n(quantity))?;
+
+                    let item = s.items.get_mut(spec).unwrap();
+                    item.quantity = item.quantity.checked_add(quantity)?;
+                    s.apply_buy_effects(spec, quantity)?;
+                    Some(true)
+                })
+                .unwrap_or(true)
+            }
+            Msg::Sell { quantity, spec } => {
+                let item = &self.items[spec];
+                if !item.can_sell() {
+                    return false;
+                }
+                let quantity = cmp::min(quantity, item.quantity);
+                if quantity <= 0 {
+                    return false;
+                }
+
+                self.atomically("selling items", |s| {
+                    let item = s.items.get_mut(spec).unwrap();
+                    let refund = item.refund_n(quantity);
+                    item.quantity -= quantity;
+                    s.receive(refund)?;
+                    Some(true)
+                })
+                .unwrap_or(true)
+            }
+            Msg::ConsumeEvent { spec } => {
+                // those need an answer
+                if !spec.choices.is_empty() {
+                    return false;
+                }
+                if let Some(ev) = self.events.get_mut(spec) {
+                    ev.consumed = true;
+                }
+                if let Some(entry) = self.latest_entry_mut(spec) {
+                    entry.read = true;
+                }
+                true
+            }
+            Msg::Read { entry } => {
+                if !self.can_read(entry) {
+                    return false;
+                }
+                let index = self.entry_index(entry).unwrap();
+                if self.is_waiting(index) {
+                    self.events
+                        .get_mut(self.inbox[index].spec)
+                        .unwrap()
+                        .consumed = true;
+                }
+                self.inbox[index].read = true;
+                true
+            }
+            Msg::Choose { spec, choice } => {
+                if !self.can_choose(spec, choice) {
+                    return false;
+                }
+
+                self.atomically("answering an event", |s| {
+                    let choice = &spec.choices[choice];
+                    for effect in &choice.effects {
+                        s.apply_effect(effect)?;
+                    }
+                    let ev = s.events.get_mut(spec).unwrap();
+                    ev.consumed = true;
+                    ev.chosen = Some(choice);
+                    if let Some(entry) = s.latest_entry_mut(spec) {
+                        entry.read = true;
+                        entry.chosen = Some(choice);
+                    }
+                    Some(true)
+                })
+                .unwrap_or(true)
+            }
+            Msg::PurchaseUpgrade { spec } => {
+                if !self.can_buy_upgrade(spec) {
+                    return false;
+                }
+
+                self.atomically("buying an upgrade", |s| {
+                    s.spend(spec.cost)?;
+                    s.upgrades.get_mut(spec).unwrap().bought = true;
+                    s.apply_upgrade(spec);
+                    Some(true)
+                })
+                .unwrap_or(true)
+            }
+            Msg::Repopulate => {
+                if !self.can_repopulate() {
+                    return false;
+                }
+
+                self.atomically("repopulating", |s| {
+                    s.heaven.favor = s.heaven.favor.checked_sub(favor::REPOPULATION_FAVOR)?;
+                    s.alive = favor::REPOPULATION;
+                    Some(true)
+                })
+                .unwrap_or(true)
+            }
+        }
+    }
+
+    /// Advances the simulation by one month, unless a modal event is
+    /// waiting to be acknowledged. Toasts don't hold anything up.
+    pub fn tick(&mut self) -> bool {
+        if self.has_active_events() {
+            return false;
+        }
+
+        self.atomically("running the month", |s| s.advance().map(|_| true))
+            .unwrap_or(true)
+    }
+
+    /// Runs `f`, which returns whether anything changed. Should any of its
+    /// arithmetic overflow, everything it did is undone, the player gets
+    /// an error notice instead, and this returns `None`.
+    fn atomically(
+        &mut self,
+        action: &str,
+        f: impl FnOnce(&mut Self) -> Option<bool>,
+    ) -> Option<bool> {
+        let before = self.clone();
+        let changed = f(self).and_then(|changed| {
+            if changed {
+                self.schedule_events()?;
+            }
+            Some(changed)
+        });
+        if changed.is_none() {
+            *self = before;
+            self.report_overflow(action);
+        }
+        changed
+    }
+
+    fn report_overflow(&mut self, action: &str) {
+        let mut ev = registry().builtin_event("overflow").instantiate();
+        ev.details = Some(Details::Overflow {
+            action: action.to_owned(),
+        });
+        self.push_event(ev);
+    }
+
+    /// Simulates `months` months in one go, for when the game wasn't
+    /// running (closed tab, etc.). Events don't pause anything here, and
+    /// the player gets a summary event at the end.
+    ///
+    /// Like `atomically`, but a snapshot covers a whole batch of months.
+    /// Should one of them overflow, the batch is replayed from its
+    /// snapshot up to the month before, which the saved `rng` makes go
+    /// exactly as it did the first time.
+    pub fn fast_forward(&mut self, months: i64) -> OfflineReport {
+        let souls = self.souls();
+        let alive = self.alive;
+        let due = self.due;
+
+        let month = |s: &mut Self| s.advance().and_then(|_| s.schedule_events());
+        let mut simulated = 0;
+        while simulated < months {
+            let batch = cmp::min(CATCH_UP_BATCH, months - simulated);
+            let before = self.clone();
+            let done = (0..batch).take_while(|_| month(self).is_some()).count() as i64;
+            simulated += done;
+            if done < batch {
+                *self = before;
+                for _ in 0..done {
+                    month(self);
+                }
+                self.report_overflow("catching up");
+                break;
+            }
+        }
+
+        let report = OfflineReport {
+            months: simulated,
+            souls: self.souls().saturating_sub(souls),
+            population: self.alive.saturating_sub(alive),
+            corpses: self.due.saturating_sub(due),
+        };
+
+        if report.months > 0 {
+            let mut ev = registry().builtin_event("away_report").instantiate();
+            ev.details = Some(Details::Away(report));
+            self.push_event(ev);
+        }
+        report
+    }
+
+    fn advance(&mut self) -> Option<()> {
+        let month = self.month;
+        self.modifiers.retain(|m| m.until > month);
+
+        let was_extinct = self.is_extinct();
+        let deaths = self.round_randomly(self.expected_deaths());
+        let deaths = cmp::min(deaths, self.alive);
+
+        self.due = self.due.checked_add(deaths)?;
+        self.alive = self.alive.checked_sub(deaths)?;
+
+        let births = self.round_randomly(self.expected_births());
+        self.alive = self.alive.checked_add(births)?;
+
+        self.month += 1;
+        self.notice_extinction(was_extinct);
+        self.roll_random_event()?;
+
+        self.harvest(self.souls_per_tick())?;
+        self.pay_interest()?;
+        self.goodness += (self.target_goodness() - self.goodness) * GOODNESS_DRIFT;
+        self.update_items_reveal();
+        self.update_upgrades_reveal();
+        Some(())
+    }
+
+    fn add_item(&mut self, spec: &'static ItemSpec, quantity: i64) {
+        let item = spec.instantiate(quantity);
+        self.items.insert(item.spec, item);
+    }
+
+    fn add_upgrade(&mut self, spec: &'static UpgradeSpec) {
+        let upgrade = spec.instantiate();
+        self.upgrades.insert(upgrade.spec, upgrade);
+    }
+
+    fn add_event(&mut self, spec: &'static EventSpec) {
+        self.push_event(spec.instantiate());
+    }
+
+    /// Puts `event` at the back of the queue, even if it fired before,
+    /// and in the inbox.
+    fn push_event(&mut self, mut event: Event) {
+        event.month = self.month;
+        let spec = event.spec;
+        let id = self.inbox.last().map(|entry| entry.id + 1).unwrap_or(0);
+        self.inbox.push(InboxEntry {
+            id,
+            spec,
+            month: event.month,
+            details: event.details.clone(),
+            chosen: None,
+            read: false,
+        });
+        self.events.retain(|&other, _| other != spec);
+        self.events.insert(spec, event);
+        self.trim_inbox();
+    }
+
+    /// Drops the oldest entries past `MAX_INBOX`, leaving the ones still
+    /// waiting for the player.
+    pub(crate) fn trim_inbox(&mut self) {
+        while self.inbox.len() > MAX_INBOX {
+            match (0..self.inbox.len()).find(|&index| !self.is_waiting(index)) {
+                Some(index) => {
+                    self.inbox.remove(index);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn entry_index(&self, id: u64) -> Option<usize> {
+        self.inbox.binary_search_by_key(&id, |entry| entry.id).ok()
+    }
+
+    /// The inbox entry for the last time `spec` fired.
+    fn latest_entry_mut(&mut self, spec: &EventSpec) -> Option<&mut InboxEntry> {
+        self.inbox.iter_mut().rev().find(|entry| entry.spec == spec)
+    }
+
+    /// Whether inbox entry `index` is the event in the queue, rather than
+    /// an earlier time it fired.
+    fn is_waiting(&self, index: usize) -> bool {
+        let spec = self.inbox[index].spec;
+        let latest = self.inbox.iter().rposition(|entry| entry.spec == spec);
+        let queued = self
+            .events
+            .get(spec)
+            .map(|ev| !ev.consumed)
+            .unwrap_or(false);
+        latest == Some(index) && queued
+    }
+
+    /// Whether inbox entry `id` can be opened. Modal events waiting for
+    /// the player are on screen already.
+    pub fn can_read(&self, id: u64) -> bool {
+        match self.entry_index(id) {
+            Some(index) => self.inbox[index].spec.toast || !self.is_waiting(index),
+            None => false,
+        }
+    }
+
+    pub fn customer(&self, kind: CustomerKind) -> &Customer {
+        match kind {
+            CustomerKind::Heaven => &self.heaven,
+            CustomerKind::Hell => &self.hell,
+        }
+    }
+
+    pub fn customer_mut(&mut self, kind: CustomerKind) -> &mut Customer {
+        match kind {
+            CustomerKind::Heaven => &mut self.heaven,
+            CustomerKind::Hell => &mut self.hell,
+        }
+    }
+
+    /// Items that can currently be bought in `category`.
+    pub fn visible_items(&self, category: items::ItemCategory) -> impl Iterator<Item = &Item> {
+        self.items
+            .values()
+            .filter(move |item| item.revealed && item.spec.category == category && !item.is_done())
+    }
+
+    /// Unique items in `category` that were already triggered.
+    pub fn done_items(&self, category: items::ItemCategory) -> impl Iterator<Item = &Item> {
+        self.items
+            .values()
+            .filter(move |item| item.spec.category == category && item.is_done())
+    }
+
+    pub fn visible_upgrades(&self) -> impl Iterator<Item = &Upgrade> {
+        self.upgrades
+            .values()
+            .filter(move |up| up.revealed && !up.bought)
+    }
+
+    pub fn effective_birth_rate(&self) -> f64 {
+        let factor = self.sum_factor(|i| i.spec.br_mod)
+            + self.modifiers.iter().map(|m| m.birth).sum::<f64>();
+        (self.base_birth_rate * factor).max(0.0)
+    }
+
+    pub fn effective_death_rate(&self) -> f64 {
+        let factor = self.sum_factor(|i| i.spec.dr_mod)
+            + self.modifiers.iter().map(|m| m.death).sum::<f64>();
+        (self.base_death_rate * factor).max(0.0)
+    }
+
+    fn sum_factor(&self, f: fn(item: &Item) -> Option<f64>) -> f64 {
+        let mut factor = 1.0;
+        for item in self.items.values() {
+            if let Some(q) = f(item) {
+                factor += q * item.quantity as f64;
+            }
+        }
+        factor
+    }
+
+    pub fn births_per_tick(&self) -> Souls {
+        self.expected_births().round()
+    }
+
+    /// Never more than are alive, so the population can actually reach
+    /// zero instead of dying one soul at a time forever.
+    pub fn deaths_per_tick(&self) -> Souls {
+        cmp::min(self.expected_deaths().round(), self.alive)
+    }
+
+    fn expected_births(&self) -> Souls {
+        self.alive
+            .saturating_scale(self.effective_birth_rate() / 1000.0 / DAYS_PER_YEAR * DAYS_PER_TICK)
+    }
+
+    fn expected_deaths(&self) -> Souls {
+        self.alive
+            .saturating_scale(self.effective_death_rate() / 1000.0 / DAYS_PER_YEAR * DAYS_PER_TICK)
+    }
+
+    /// Rounds up as often as the fraction says, so a third of a death a
+    /// month still kills someone every three months or so.
+    fn round_randomly(&mut self, souls: Souls) -> Souls {
+        let whole = souls.trunc();
+        let fraction = souls.saturating_sub(whole).float();
+        if fraction > 0.0 && self.rng.next_f64() < fraction {
+            whole.saturating_add(Souls::new(1))
+        } else {
+            whole
+        }
+    }
+
+    pub fn souls_per_tick(&self) -> Souls {
+        let mut total = Souls::ZERO;
+        for item in self.items.values() {
+            if let Some(x) = item.spec.get_spt(self) {
+                total = total.saturating_add(x.multiply(item.quantity));
+            }
+        }
+        total
+    }
+
+    /// Monthly interest rate on banked souls, summed over finance items.
+    pub fn interest_rate(&self) -> f64 {
+        let mut rate = 0.0;
+        for item in self.items.values() {
+            if let Some(x) = item.spec.get_interest(self) {
+                rate += x * item.quantity as f64;
+            }
+        }
+        rate
+    }
+
+    pub fn interest_per_tick(&self) -> Souls {
+        let rate = self.interest_rate();
+        self.virtuous
+            .saturating_scale(rate)
+            .trunc()
+            .saturating_add(self.sinful.saturating_scale(rate).trunc())
+    }
+
+    fn pay_interest(&mut self) -> Option<()> {
+        let rate = self.interest_rate();
+        let virtuous = self.virtuous.checked_scale(rate)?.trunc();
+        let sinful = self.sinful.checked_scale(rate)?.trunc();
+        self.virtuous = self.virtuous.checked_add(virtuous)?;
+        self.sinful = self.sinful.checked_add(sinful)?;
+        Some(())
+    }
+
+    /// Extra fraction credited on remittances, summed over finance items.
+    pub fn remit_bonus(&self) -> f64 {
+        self.sum_factor(|i| i.spec.remit_mod) - 1.0
+    }
+
+    /// How much a customer gets credited when we remit `quantity` souls.
+    pub fn remit_credit(&self, quantity: Souls) -> Souls {
+        quantity.saturating_scale(1.0 + self.remit_bonus()).trunc()
+    }
+
+    pub fn souls_per_click(&self) -> Souls {
+        if self.cheat {
+            return Souls::B;
+        }
+
+        let mut total = Souls::new(1);
+        for item in self.items.values() {
+            if let Some(x) = item.spec.get_spc(self) {
+                total = total.saturating_add(x.multiply(item.quantity));
+            }
+        }
+        total
+    }
+
+    pub fn item_quantity(&self, item: &ItemSpec) -> i64 {
+        if let Some(item) = self.items.get(item) {
+            item.quantity
+        } else {
+            0
+        }
+    }
+
+    /// All the souls we hold, virtuous or not.
+    pub fn souls(&self) -> Souls {
+        self.virtuous.saturating_add(self.sinful)
+    }
+
+    /// Souls a realm will accept: Heaven only wants the virtuous ones,
+    /// Hell only the sinful ones.
+    pub fn remittable(&self, kind: CustomerKind) -> Souls {
+        match kind {
+            CustomerKind::Heaven => self.virtuous,
+            CustomerKind::Hell => self.sinful,
+        }
+    }
+
+    /// Where `goodness` is headed, given everything we've bought.
+    pub fn target_goodness(&self) -> f64 {
+        self.sum_factor(|i| i.spec.goodness_mod).clamp(0.0, 1.0)
+    }
+
+    /// Collects up to `quantity` souls from the corpses waiting.
+    fn harvest(&mut self, quantity: Souls) -> Option<()> {
+        let harvested = cmp::min(self.due, quantity);
+        self.due = self.due.checked_sub(harvested)?;
+
+        let virtuous = harvested.checked_scale(self.goodness)?.round();
+        let sinful = harvested.checked_sub(virtuous)?;
+        self.virtuous = self.virtuous.checked_add(virtuous)?;
+        self.sinful = self.sinful.checked_add(sinful)?;
+        Some(())
+    }
+
+    /// Takes `cost` out of both pools, proportionally to their size, so
+    /// shopping doesn't change the virtuous/sinful ratio. Fails rather
+    /// than leaving either pool negative.
+    fn spend(&mut self, cost: Souls) -> Option<()> {
+        let total = self.souls();
+        if cost > total {
+            return None;
+        }
+        if cost.is_zero() {
+            return Some(());
+        }
+
+        let from_virtuous = cost.checked_scale(self.virtuous.ratio(total))?.trunc();
+        let from_virtuous = cmp::min(from_virtuous, self.virtuous);
+        let from_sinful = cmp::min(cost.checked_sub(from_virtuous)?, self.sinful);
+        let from_virtuous = cost.checked_sub(from_sinful)?;
+
+        let virtuous = self.virtuous.checked_sub(from_virtuous)?;
+        let sinful = self.sinful.checked_sub(from_sinful)?;
+        if virtuous.is_negative() || sinful.is_negative() {
+            return None;
+        }
+        self.virtuous = virtuous;
+        self.sinful = sinful;
+        Some(())
+    }
+
+    /// Puts `souls` back into both pools, proportionally to their size (or
+    /// to `goodness` if both are empty), the reverse of `spend`.
+    fn receive(&mut self, souls: Souls) -> Option<()> {
+        let total = self.souls();
+        let share = if total.is_zero() {
+            self.goodness
+        } else {
+            self.virtuous.ratio(total)
+        };
+        let virtuous = souls.checked_scale(share)?.trunc();
+        let sinful = souls.checked_sub(virtuous)?;
+        self.virtuous = self.virtuous.checked_add(virtuous)?;
+        self.sinful = self.sinful.checked_add(sinful)?;
+        Some(())
+    }
+
+    fn update_items_reveal(&mut self) {
+        let mut revealed = Vec::new();
+        for item in self.items.values() {
+            if !item.revealed
+                && (item.quantity > 0
+                    || (self.souls() >= item.spec.cost / 2 && item.spec.favor_allows(self)))
+            {
+                revealed.push(item.spec);
+            }
+        }
+
+        for spec in revealed {
+            self.items.get_mut(spec).unwrap().revealed = true;
+        }
+    }
+
+    fn update_upgrades_reveal(&mut self) {
+        let mut revealed = Vec::new();
+        for upgrade in self.upgrades.values() {
+            if !upgrade.revealed
+                && self.upgrade_unlocked(upgrade.spec)
+                && self.souls() >= upgrade.spec.cost / 2
+            {
+                revealed.push(upgrade.spec);
+            }
+        }
+
+        for spec in revealed {
+            self.upgrades.get_mut(spec).unwrap().revealed = true;
+        }
+    }
+
+    pub fn upgrade_bought(&self, spec: &UpgradeSpec) -> bool {
+        self.upgrades.get(spec).map(|up| up.bought).unwrap_or(false)
+    }
+
+    /// Whether the upgrade's prerequisite, if any, was bought.
+    pub fn upgrade_unlocked(&self, spec: &UpgradeSpec) -> bool {
+        spec.requires
+            .map(|req| self.upgrade_bought(req))
+            .unwrap_or(true)
+    }
+
+    pub fn can_buy_upgrade(&self, spec: &UpgradeSpec) -> bool {
+        self.upgrades.contains_key(spec)
+            && !self.upgrade_bought(spec)
+            && self.upgrade_unlocked(spec)
+            && spec.cost <= self.souls()
+    }
+
+    /// Effects of having just bought `bought` units.
+    fn apply_buy_effects(&mut self, spec: &ItemSpec, bought: i64) -> Option<()> {
+        let was_extinct = self.is_extinct();
+        if let Some(mult) = spec.pop_multiplier {
+            let mult = mult.powf(bought as f64);
+            self.alive = self.alive.checked_scale(mult)?.trunc();
+        }
+        if let Some(r) = spec.pop_kill_ratio {
+            let survival = (1.0 - r).powf(bought as f64);
+            let survivors = self.alive.checked_scale(survival)?.trunc();
+            let deaths = self.alive.checked_sub(survivors)?;
+            self.alive = survivors;
+            self.due = self.due.checked_add(deaths)?;
+        }
+        self.notice_extinction(was_extinct);
+
+        Some(())
+    }
+
+    /// Nobody left alive: no births, no deaths, only the corpses already
+    /// there to harvest.
+    pub fn is_extinct(&self) -> bool {
+        self.alive <= Souls::ZERO
+    }
+
+    /// Tells the player when the population just died out.
+    fn notice_extinction(&mut self, was_extinct: bool) {
+        if !was_extinct && self.is_extinct() {
+            self.add_event(registry().builtin_event("extinction"));
+        }
+    }
+
+    pub fn can_repopulate(&self) -> bool {
+        self.is_extinct() && self.heaven.favor >= favor::REPOPULATION_FAVOR
+    }
+
+    /// Fires every content event whose triggers all hold. Each event fires
+    /// at most once per game.
+    fn schedule_events(&mut self) -> Option<()> {
+        let due: Vec<_> = registry()
+            .events
+            .iter()
+            .cloned()
+            .filter(|&spec| !self.events.contains_key(spec) && spec.triggered(self))
+            .collect();
+        for spec in due {
+            self.fire_event(spec)?;
+        }
+        Some(())
+    }
+
+    /// Fires at most one random event. A single roll decides, so each
+    /// event gets exactly its `chance`.
+    fn roll_random_event(&mut self) -> Option<()> {
+        let roll = self.rng.next_f64();
+        let mut cumulative = 0.0;
+        for &spec in &registry().events {
+            if !spec.may_roll(self) {
+                continue;
+            }
+            cumulative += spec.chance;
+            if roll < cumulative {
+                return self.fire_event(spec);
+            }
+        }
+        Some(())
+    }
+
+    /// Adds `spec` to the queue and applies its `effects`.
+    fn fire_event(&mut self, spec: &'static EventSpec) -> Option<()> {
+        self.add_event(spec);
+        for effect in &spec.effects {
+            self.apply_effect(effect)?;
+        }
+        Some(())
+    }
+
+    /// Whether `spec` is waiting for an answer and the player can afford
+    /// its `choice`th one.
+    pub fn can_choose(&self, spec: &EventSpec, choice: usize) -> bool {
+        let waiting = self
+            .events
+            .get(spec)
+            .map(|ev| !ev.consumed)
+            .unwrap_or(false);
+        match spec.choices.get(choice) {
+            Some(choice) => waiting && choice.cost() <= self.souls(),
+            None => false,
+        }
+    }
+
+    fn apply_effect(&mut self, effect: &Effect) -> Option<()> {
+        match *effect {
+            Effect::Souls(q) if q.is_negative() => self.spend(Souls::ZERO.checked_sub(q)?)?,
+            Effect::Souls(q) => self.receive(q)?,
+            Effect::Goodness(d) => self.goodness = (self.goodness + d).clamp(0.0, 1.0),
+            Effect::BirthRate(d) => self.base_birth_rate = (self.base_birth_rate + d).max(0.0),
+            Effect::RevealItem(spec) => self.items.get_mut(spec)?.revealed = true,
+            Effect::RevealUpgrade(spec) => self.upgrades.get_mut(spec)?.revealed = true,
+            Effect::Event(spec) => self.fire_event(spec)?,
+            Effect::Kill(ratio) => {
+                let was_extinct = self.is_extinct();
+                let deaths = cmp::min(self.alive, self.alive.checked_scale(ratio)?.trunc());
+                self.alive = self.alive.checked_sub(deaths)?;
+                self.due = self.due.checked_add(deaths)?;
+                self.notice_extinction(was_extinct);
+            }
+            Effect::Births(ratio) => {
+                let births = self.alive.checked_scale(ratio)?.trunc();
+                self.alive = self.alive.checked_add(births)?;
+            }
+            Effect::Rates {
+                birth,
+                death,
+                months,
+            } => self.modifiers.push(Modifier {
+                birth,
+                death,
+                until: self.month.saturating_add(months),
+            }),
+        }
+        Some(())
+    }
+
+    /// Modal events waiting to be acknowledged, oldest first. Only the
+    /// first one is shown at a time.
+    pub fn queued_events(&self) -> impl Iterator<Item = &Event> {
+        self.events
+            .values()
+            .filter(|ev| !ev.consumed && !ev.spec.toast)
+    }
+
+    /// Toasts that weren't dismissed yet, oldest first.
+    pub fn toasts(&self) -> impl Iterator<Item = &Event> {
+        self.events
+            .values()
+            .filter(|ev| !ev.consumed && ev.spec.toast)
+    }
+
+    /// Inbox entries the player hasn't read yet, toasts included.
+    pub fn unread_events(&self) -> usize {
+        self.inbox.iter().filter(|entry| !entry.read).count()
+    }
+
+    /// Whether a modal event is waiting, which pauses the game.
+    pub fn has_active_events(&self) -> bool {
+        self.queued_events().next().is_some()
+    }
+
+    pub(crate) fn apply_upgrade(&mut self, spec: &'static UpgradeSpec) {
+        for effect in spec.effects.as_slice() {
+            self.effects.entry(effect.spec).or_default().push(effect);
+        }
+    }
+}
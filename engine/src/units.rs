@@ -0,0 +1,618 @@
+use num_format::ToFormattedString;
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops;
+
+pub use num_format::Locale;
+
+/// Mantissas stay below this. Smaller amounts are stored with a zero
+/// exponent, which keeps them exact, since f64 holds every integer up to
+/// 2^53.
+const LIMIT: f64 = 1e15;
+
+/// Anything past `10^MAX_EXPONENT` counts as an overflow. Far beyond what
+/// any game will reach, but it keeps exponent arithmetic from wrapping.
+const MAX_EXPONENT: i64 = 1 << 60;
+
+const OVERFLOW: &str = "souls overflow";
+
+/// Suffixes for each power of 1000, starting at a million. Past the last
+/// one, amounts are shown in scientific notation.
+const SUFFIXES: &[&str] = &["M", "B", "T", "Qa", "Qi", "Sx", "Sp", "Oc", "No", "Dc"];
+
+/// Same as `SUFFIXES`, spelled out.
+const NAMES: &[&str] = &[
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+    "sextillion",
+    "septillion",
+    "octillion",
+    "nonillion",
+    "decillion",
+];
+
+/// How big amounts are written out. Anything below a million is always
+/// written in full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    /// 1.23 M
+    Short,
+    /// 1.23 million
+    Long,
+    /// 1.23e6
+    Scientific,
+    /// 1.23e6, but the exponent is a multiple of 3
+    Engineering,
+    /// 1,230,000, as long as amounts are exact
+    Full,
+}
+
+impl Notation {
+    pub const ALL: &'static [Self] = &[
+        Notation::Short,
+        Notation::Long,
+        Notation::Scientific,
+        Notation::Engineering,
+        Notation::Full,
+    ];
+
+    /// Stable name, for settings storage.
+    pub fn key(self) -> &'static str {
+        match self {
+            Notation::Short => "short",
+            Notation::Long => "long",
+            Notation::Scientific => "scientific",
+            Notation::Engineering => "engineering",
+            Notation::Full => "full",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().cloned().find(|n| n.key() == key)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Notation::Short => "Short",
+            Notation::Long => "Long",
+            Notation::Scientific => "Scientific",
+            Notation::Engineering => "Engineering",
+            Notation::Full => "Full",
+        }
+    }
+}
+
+/// A notation, plus the locale that decides digit grouping and the
+/// decimal separator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub notation: Notation,
+    pub locale: Locale,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            notation: Notation::Short,
+            locale: Locale::en,
+        }
+    }
+}
+
+/// `Souls` written out in a given `NumberFormat`, see `Souls::display`.
+pub struct Formatted {
+    souls: Souls,
+    format: NumberFormat,
+}
+
+/// An amount of souls, `mantissa * 10^exponent`.
+///
+/// Incremental games grow exponentially, so this has no upper bound.
+/// Amounts below 10^15 are whole numbers and exact; above that they're
+/// approximate, which nobody will notice.
+///
+/// The operators panic when the result can't be represented, like integer
+/// arithmetic does in debug builds. The engine sticks to the `checked_*`
+/// and `saturating_*` methods instead.
+#[derive(Clone, Copy, Debug)]
+pub struct Souls {
+    mantissa: f64,
+    exponent: i64,
+}
+
+impl Souls {
+    pub const ZERO: Self = Self::new(0);
+    pub const K: Self = Self::new(1_000);
+    pub const M: Self = Self::new(1_000_000);
+    pub const B: Self = Self::new(1_000_000_000);
+    pub const T: Self = Self::new(1_000_000_000_000);
+    pub const MAX: Self = Self {
+        mantissa: LIMIT - 1.0,
+        exponent: MAX_EXPONENT,
+    };
+    pub const MIN: Self = Self {
+        mantissa: 1.0 - LIMIT,
+        exponent: MAX_EXPONENT,
+    };
+
+    pub const fn new(n: i64) -> Self {
+        Self::normalized(n as f64, 0)
+    }
+
+    /// Drops the fraction, like casting to an integer would.
+    pub fn from_f64(x: f64) -> Self {
+        Self::checked(x, 0).expect(OVERFLOW).trunc()
+    }
+
+    /// `normalized`, unless the amount can't be represented.
+    fn checked(mantissa: f64, exponent: i64) -> Option<Self> {
+        if !mantissa.is_finite() || exponent.abs() > 2 * MAX_EXPONENT {
+            return None;
+        }
+        let souls = Self::normalized(mantissa, exponent);
+        if souls.exponent > MAX_EXPONENT {
+            return None;
+        }
+        Some(souls)
+    }
+
+    /// Brings the mantissa back in range: below `LIMIT`, and no smaller
+    /// than it has to be when the exponent is positive.
+    const fn normalized(mut mantissa: f64, mut exponent: i64) -> Self {
+        if mantissa == 0.0 || exponent < -30 {
+            return Self {
+                mantissa: 0.0,
+                exponent: 0,
+            };
+        }
+        while mantissa >= LIMIT || mantissa <= -LIMIT {
+            mantissa /= 10.0;
+            exponent += 1;
+        }
+        while exponent > 0 && mantissa < LIMIT / 10.0 && mantissa > -LIMIT / 10.0 {
+            mantissa *= 10.0;
+            exponent -= 1;
+        }
+        while exponent < 0 {
+            mantissa /= 10.0;
+            exponent += 1;
+        }
+        Self {
+            // turns -0.0 into 0.0, so equal amounts hash the same
+            mantissa: mantissa + 0.0,
+            exponent,
+        }
+    }
+
+    /// Closest f64, which is infinite for truly astronomical amounts.
+    pub fn float(self) -> f64 {
+        if self.exponent > 400 {
+            return self.mantissa * f64::INFINITY;
+        }
+        self.mantissa * 10f64.powi(self.exponent as i32)
+    }
+
+    pub fn times(self, x: i64) -> Self {
+        self * x
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let (big, small) = if self.exponent >= rhs.exponent {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+        let shift = big.exponent - small.exponent;
+        if shift > 30 {
+            return Some(big);
+        }
+        Self::checked(
+            big.mantissa + small.mantissa / 10f64.powi(shift as i32),
+            big.exponent,
+        )
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(-rhs)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        Self::checked(self.mantissa * rhs.mantissa, self.exponent + rhs.exponent)
+    }
+
+    pub fn checked_times(self, x: i64) -> Option<Self> {
+        Self::checked(self.mantissa * x as f64, self.exponent)
+    }
+
+    /// `None` when dividing by zero, rounds towards zero otherwise.
+    pub fn checked_div(self, x: i64) -> Option<Self> {
+        if x == 0 {
+            return None;
+        }
+        Self::checked(self.mantissa / x as f64, self.exponent).map(Self::trunc)
+    }
+
+    /// Multiplies by `x`, keeping any fraction of a soul. Follow up with
+    /// `trunc`, `round` or `ceil`; `*` truncates. `None` if `x` isn't a
+    /// finite number or the result overflows.
+    pub fn checked_scale(self, x: f64) -> Option<Self> {
+        Self::checked(self.mantissa * x, self.exponent)
+    }
+
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        self.checked_add(rhs)
+            .unwrap_or_else(|| self.saturate(rhs.is_negative()))
+    }
+
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs)
+            .unwrap_or_else(|| self.saturate(!rhs.is_negative()))
+    }
+
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        let negative = self.is_negative() != rhs.is_negative();
+        self.checked_mul(rhs)
+            .unwrap_or_else(|| self.saturate(negative))
+    }
+
+    pub fn saturating_times(self, x: i64) -> Self {
+        let negative = self.is_negative() != (x < 0);
+        self.checked_times(x)
+            .unwrap_or_else(|| self.saturate(negative))
+    }
+
+    /// Like `checked_scale`, but a factor that isn't a number gives zero.
+    pub fn saturating_scale(self, x: f64) -> Self {
+        if x.is_nan() {
+            return Self::ZERO;
+        }
+        let negative = self.is_negative() != (x < 0.0);
+        self.checked_scale(x)
+            .unwrap_or_else(|| self.saturate(negative))
+    }
+
+    fn saturate(self, negative: bool) -> Self {
+        if negative {
+            Self::MIN
+        } else {
+            Self::MAX
+        }
+    }
+
+    /// `self * factor^n`, even when `factor^n` doesn't fit an f64. Keeps
+    /// any fraction, like `checked_scale`, and saturates at `MAX`.
+    pub fn times_pow(self, factor: f64, n: f64) -> Self {
+        let growth = factor.powf(n);
+        if growth < 1e200 {
+            return self.saturating_scale(growth);
+        }
+        let log = n * factor.log10();
+        let whole = log.floor();
+        if !whole.is_finite() || whole > MAX_EXPONENT as f64 {
+            return self.saturate(self.is_negative());
+        }
+        Self::checked(
+            self.mantissa * 10f64.powf(log - whole),
+            self.exponent + whole as i64,
+        )
+        .unwrap_or_else(|| self.saturate(self.is_negative()))
+    }
+
+    /// `self / rhs`, as a plain ratio.
+    pub fn ratio(self, rhs: Self) -> f64 {
+        let shift = (self.exponent - rhs.exponent).clamp(-400, 400);
+        self.mantissa / rhs.mantissa * 10f64.powi(shift as i32)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.mantissa == 0.0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.mantissa < 0.0
+    }
+
+    /// Base 10 logarithm of the absolute amount, minus infinity for zero.
+    pub fn log10(self) -> f64 {
+        self.mantissa.abs().log10() + self.exponent as f64
+    }
+
+    pub fn trunc(self) -> Self {
+        self.whole(f64::trunc)
+    }
+
+    pub fn round(self) -> Self {
+        self.whole(f64::round)
+    }
+
+    pub fn ceil(self) -> Self {
+        self.whole(f64::ceil)
+    }
+
+    /// Fractions only exist below `LIMIT`, anything bigger is whole already.
+    fn whole(self, f: fn(f64) -> f64) -> Self {
+        if self.exponent == 0 {
+            Self::normalized(f(self.mantissa), 0)
+        } else {
+            self
+        }
+    }
+
+    pub fn display(self, format: NumberFormat) -> Formatted {
+        Formatted {
+            souls: self,
+            format,
+        }
+    }
+
+    /// Splits into a mantissa in `[1, 10)` and a power of ten.
+    fn scientific(self) -> (f64, i64) {
+        if self.is_zero() {
+            return (0.0, 0);
+        }
+        let mut digits = self.mantissa.abs().log10().floor() as i64;
+        let mut mantissa = self.mantissa / 10f64.powi(digits as i32);
+        // log10 can be off by a hair around exact powers of ten
+        if mantissa.abs() >= 10.0 {
+            mantissa /= 10.0;
+            digits += 1;
+        } else if mantissa.abs() < 1.0 {
+            mantissa *= 10.0;
+            digits -= 1;
+        }
+        (mantissa, self.exponent + digits)
+    }
+}
+
+impl Default for Souls {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<i64> for Souls {
+    fn from(x: i64) -> Self {
+        Self::new(x)
+    }
+}
+
+/// Saturates at `i64::MAX` for bigger amounts.
+impl From<Souls> for i64 {
+    fn from(s: Souls) -> i64 {
+        s.float() as i64
+    }
+}
+
+impl PartialEq for Souls {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.cmp(rhs) == Ordering::Equal
+    }
+}
+
+impl Eq for Souls {}
+
+impl PartialOrd for Souls {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl Ord for Souls {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        let sign = |s: &Self| s.mantissa.partial_cmp(&0.0).unwrap_or(Ordering::Equal);
+        let (lsign, rsign) = (sign(self), sign(rhs));
+        if lsign != rsign {
+            return lsign.cmp(&rsign);
+        }
+
+        // Normalized, so a bigger exponent means a bigger amount.
+        let magnitude = self.exponent.cmp(&rhs.exponent).then_with(|| {
+            self.mantissa
+                .abs()
+                .partial_cmp(&rhs.mantissa.abs())
+                .unwrap_or(Ordering::Equal)
+        });
+        if lsign == Ordering::Less {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl Hash for Souls {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mantissa.to_bits().hash(state);
+        self.exponent.hash(state);
+    }
+}
+
+impl ops::Neg for Souls {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::normalized(-self.mantissa, self.exponent)
+    }
+}
+
+impl ops::Mul for Souls {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs).expect(OVERFLOW)
+    }
+}
+
+impl ops::Add for Souls {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).expect(OVERFLOW)
+    }
+}
+
+impl ops::Sub for Souls {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).expect(OVERFLOW)
+    }
+}
+
+impl ops::Mul<i64> for Souls {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Souls {
+        self.checked_times(rhs).expect(OVERFLOW)
+    }
+}
+
+impl ops::Mul<f64> for Souls {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Souls {
+        self.checked_scale(rhs).expect(OVERFLOW).trunc()
+    }
+}
+
+impl ops::Div<i64> for Souls {
+    type Output = Self;
+
+    fn div(self, rhs: i64) -> Souls {
+        self.checked_div(rhs).expect("souls divided by zero")
+    }
+}
+
+impl ops::Mul<Souls> for i64 {
+    type Output = Souls;
+
+    fn mul(self, rhs: Souls) -> Souls {
+        rhs * self
+    }
+}
+
+impl ops::AddAssign for Souls {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl ops::SubAssign for Souls {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl fmt::Display for Souls {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display(NumberFormat::default()))
+    }
+}
+
+/// Scientific notation past a million, `1.23e45`.
+impl fmt::LowerExp for Souls {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = NumberFormat {
+            notation: Notation::Scientific,
+            locale: Locale::en,
+        };
+        write!(f, "{}", self.display(format))
+    }
+}
+
+impl Formatted {
+    /// Two decimals, with the locale's separator.
+    fn decimal(&self, x: f64) -> String {
+        let locale = self.format.locale;
+        format!("{:.2}", x)
+            .replace('.', locale.decimal())
+            .replace('-', locale.minus_sign())
+    }
+
+    /// `mantissa * 10^exponent`, where the exponent is a multiple of
+    /// `step`, and `1 <= |mantissa| < 10^step` even after rounding.
+    fn split(&self, step: i64) -> (f64, i64) {
+        let (_, digits) = self.souls.scientific();
+        let mut exponent = digits.div_euclid(step) * step;
+        let shift = self.souls.exponent - exponent;
+        let mut mantissa = self.souls.mantissa * 10f64.powi(shift as i32);
+        // so 999.999 M shows as 1.00 B rather than 1000.00 M
+        if (mantissa * 100.0).round().abs() >= 100.0 * 10f64.powi(step as i32) {
+            mantissa /= 10f64.powi(step as i32);
+            exponent += step;
+        }
+        (mantissa, exponent)
+    }
+
+    fn exponential(&self, f: &mut fmt::Formatter, step: i64) -> fmt::Result {
+        let (mantissa, exponent) = self.split(step);
+        write!(f, "{}e{}", self.decimal(mantissa), exponent)
+    }
+
+    fn suffixed(&self, f: &mut fmt::Formatter, names: &[&str], sep: &str) -> fmt::Result {
+        let (mantissa, exponent) = self.split(3);
+        match names.get((exponent / 3 - 2) as usize) {
+            Some(name) => write!(f, "{}{}{}", self.decimal(mantissa), sep, name),
+            None => self.exponential(f, 1),
+        }
+    }
+}
+
+impl fmt::Display for Formatted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let souls = self.souls;
+        let notation = self.format.notation;
+        let exact = souls.exponent == 0;
+        if exact && (souls.mantissa.abs() < 1e6 || notation == Notation::Full) {
+            let n = souls.mantissa as i64;
+            return write!(f, "{}", n.to_formatted_string(&self.format.locale));
+        }
+
+        match notation {
+            Notation::Short => self.suffixed(f, SUFFIXES, " "),
+            Notation::Long => self.suffixed(f, NAMES, " "),
+            Notation::Scientific | Notation::Full => self.exponential(f, 1),
+            Notation::Engineering => self.exponential(f, 3),
+        }
+    }
+}
+
+/// Plain integers while they're exact, so small amounts look the same as
+/// they always have, `[mantissa, exponent]` past that.
+impl Serialize for Souls {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.exponent == 0 {
+            serializer.serialize_i64(self.mantissa as i64)
+        } else {
+            (self.mantissa, self.exponent).serialize(serializer)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SoulsRepr {
+    Integer(i64),
+    Float(f64),
+    Scientific(f64, i64),
+}
+
+impl<'de> Deserialize<'de> for Souls {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (mantissa, exponent) = match SoulsRepr::deserialize(deserializer)? {
+            SoulsRepr::Integer(n) => return Ok(Self::new(n)),
+            SoulsRepr::Float(x) => (x, 0),
+            SoulsRepr::Scientific(m, e) => (m, e),
+        };
+        Self::checked(mantissa, exponent)
+            .map(Self::trunc)
+            .ok_or_else(|| de::Error::custom("souls out of range"))
+    }
+}
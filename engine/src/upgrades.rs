@@ -0,0 +1,69 @@
+use super::items::ItemSpec;
+use super::units::*;
+
+use std::hash::{Hash, Hasher};
+
+/// A one-off improvement to an item, from `content/upgrades.ron`.
+#[derive(Debug)]
+pub struct UpgradeSpec {
+    /// Never rename, see the `content` module.
+    pub key: String,
+    pub cost: Souls,
+    pub name: String,
+    pub desc: String,
+    pub effects: Vec<UpgradeEffect>,
+    /// Upgrade that must be bought before this one is offered.
+    pub requires: Option<&'static UpgradeSpec>,
+}
+
+#[derive(Debug)]
+pub struct UpgradeEffect {
+    pub spec: &'static ItemSpec,
+    pub spc_mod: Option<f64>,
+    pub spt_mod: Option<f64>,
+    pub interest_mod: Option<f64>,
+}
+
+impl Default for UpgradeSpec {
+    fn default() -> Self {
+        Self {
+            key: String::new(),
+            cost: Souls::new(1),
+            name: "<missing>".to_owned(),
+            desc: String::new(),
+            effects: vec![],
+            requires: None,
+        }
+    }
+}
+
+impl Hash for UpgradeSpec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state)
+    }
+}
+
+impl std::cmp::PartialEq for UpgradeSpec {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.key == rhs.key
+    }
+}
+
+impl std::cmp::Eq for UpgradeSpec {}
+
+impl UpgradeSpec {
+    pub fn instantiate(&'static self) -> Upgrade {
+        Upgrade {
+            spec: self,
+            revealed: false,
+            bought: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Upgrade {
+    pub spec: &'static UpgradeSpec,
+    pub revealed: bool,
+    pub bought: bool,
+}
@@ -0,0 +1,229 @@
+use lifeclick_engine::content::registry;
+use lifeclick_engine::cost::CostCurve;
+use lifeclick_engine::units::Souls;
+
+const CURVES: &[CostCurve] = &[
+    CostCurve::Exponential(1.12),
+    CostCurve::Exponential(1.5),
+    CostCurve::Exponential(1.0),
+    CostCurve::Polynomial(2.0),
+    CostCurve::Polynomial(0.5),
+    CostCurve::Polynomial(0.0),
+    CostCurve::Stepped {
+        every: 10,
+        factor: 2.0,
+    },
+    CostCurve::Stepped {
+        every: 3,
+        factor: 1.3,
+    },
+    CostCurve::Flat,
+];
+
+const COSTS: &[i64] = &[1, 7, 15, 1_000, 2_000_000, 10_000_000_000_000];
+
+/// Past this, amounts aren't exact and neither is adding them up one by
+/// one.
+const EXACT: Souls = Souls::new(1_000_000_000_000_000);
+
+/// What buying units one at a time costs.
+fn one_by_one(curve: CostCurve, cost: Souls, from: i64, n: i64) -> Souls {
+    (from..from + n).fold(Souls::ZERO, |total, i| total + curve.ith(cost, i))
+}
+
+/// How many units buying one at a time gets, if it's less than `max`.
+fn until_broke(curve: CostCurve, cost: Souls, from: i64, budget: Souls, max: i64) -> Option<i64> {
+    let mut left = budget;
+    for n in 0..max {
+        let price = curve.ith(cost, from + n);
+        if price > left {
+            return Some(n);
+        }
+        left -= price;
+    }
+    None
+}
+
+#[test]
+fn bulk_costs_the_same_as_one_by_one() {
+    for &curve in CURVES {
+        for &cost in COSTS {
+            let cost = Souls::new(cost);
+            for &from in &[0, 5, 150] {
+                for n in 0..60 {
+                    let expected = one_by_one(curve, cost, from, n);
+                    if expected >= EXACT {
+                        break;
+                    }
+                    assert_eq!(
+                        curve.sum(cost, from, n),
+                        expected,
+                        "{:?} at {}, {} from {}",
+                        curve,
+                        cost,
+                        n,
+                        from
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn a_single_unit_costs_its_price() {
+    for &curve in CURVES {
+        for &cost in COSTS {
+            let cost = Souls::new(cost);
+            for i in (0..2_000).step_by(7) {
+                assert_eq!(
+                    curve.sum(cost, i, 1),
+                    curve.ith(cost, i),
+                    "{:?} #{}",
+                    curve,
+                    i
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn affordable_matches_one_by_one() {
+    for &curve in CURVES {
+        for &cost in COSTS {
+            let cost = Souls::new(cost);
+            for &from in &[0, 5, 150] {
+                for n in 0..40 {
+                    let exact = one_by_one(curve, cost, from, n);
+                    if exact >= EXACT {
+                        break;
+                    }
+                    for budget in &[exact - Souls::new(1), exact, exact + Souls::new(1)] {
+                        let expected = match until_broke(curve, cost, from, *budget, 5_000) {
+                            Some(expected) => expected,
+                            None => continue,
+                        };
+                        assert_eq!(
+                            curve.affordable(cost, from, *budget),
+                            expected,
+                            "{:?} at {}, {} budget from {}",
+                            curve,
+                            cost,
+                            budget,
+                            from
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn one_shots_at_any_price() {
+    let cost = Souls::new(10_000_000_000_000);
+    assert_eq!(CostCurve::Flat.affordable(cost, 0, cost), 1);
+    assert_eq!(CostCurve::Flat.affordable(cost, 0, cost - Souls::new(1)), 0);
+    assert_eq!(CostCurve::Flat.sum(cost, 0, 3), cost * 3);
+}
+
+#[test]
+fn shop_items_cost_the_same_in_bulk() {
+    for spec in &registry().items {
+        for n in 1..=10 {
+            let item = spec.instantiate(0);
+            assert_eq!(
+                item.cost_n(n),
+                one_by_one(spec.curve, spec.cost, 0, n),
+                "{} x{}",
+                spec.key,
+                n
+            );
+        }
+    }
+}
+
+/// Far enough to run out of exact terms.
+const LONG: i64 = 25_000;
+
+#[test]
+fn long_runs_of_whole_prices_stay_exact() {
+    let squares = |n: i64| n * (n + 1) * (2 * n + 1) / 6;
+    let square = CostCurve::Polynomial(2.0);
+    assert_eq!(square.sum(Souls::new(10), 0, 101), Souls::new(3_485_510));
+    for &n in &[101, 10_001, LONG] {
+        assert_eq!(
+            square.sum(Souls::new(10), 0, n),
+            Souls::new(10 * squares(n))
+        );
+        assert_eq!(
+            square.sum(Souls::new(10), 500, n),
+            Souls::new(10 * (squares(n + 500) - squares(500)))
+        );
+    }
+    assert_eq!(
+        CostCurve::Polynomial(1.0).sum(Souls::new(7), 0, LONG),
+        Souls::new(7 * LONG * (LONG + 1) / 2)
+    );
+
+    for &curve in &[
+        CostCurve::Flat,
+        CostCurve::Exponential(1.0),
+        CostCurve::Polynomial(0.0),
+        CostCurve::Stepped {
+            every: 1_000,
+            factor: 2.0,
+        },
+    ] {
+        let cost = Souls::new(7);
+        assert_eq!(
+            curve.sum(cost, 3, LONG),
+            one_by_one(curve, cost, 3, LONG),
+            "{:?}",
+            curve
+        );
+    }
+}
+
+#[test]
+fn long_runs_of_fractional_prices_never_undercharge() {
+    for &curve in &[
+        CostCurve::Polynomial(1.5),
+        CostCurve::Polynomial(0.5),
+        CostCurve::Exponential(1.0001),
+        CostCurve::Stepped {
+            every: 2,
+            factor: 1.0001,
+        },
+    ] {
+        let cost = Souls::new(1_000);
+        let sum = curve.sum(cost, 100, LONG);
+        let expected = one_by_one(curve, cost, 100, LONG);
+        assert!(sum >= expected, "{:?}: {} < {}", curve, sum, expected);
+        assert!(
+            sum <= expected + Souls::new(LONG),
+            "{:?}: {} > {}",
+            curve,
+            sum,
+            expected
+        );
+    }
+}
+
+#[test]
+fn affordable_past_the_exact_terms() {
+    for &curve in CURVES.iter().chain(&[CostCurve::Exponential(1.0001)]) {
+        let cost = Souls::new(3);
+        for &n in &[10_000, LONG] {
+            let budget = curve.sum(cost, 0, n);
+            if budget >= EXACT {
+                continue;
+            }
+            let affordable = curve.affordable(cost, 0, budget);
+            assert!(affordable >= n, "{:?}: {} for {}", curve, affordable, n);
+            assert!(curve.sum(cost, 0, affordable) <= budget, "{:?}", curve);
+            assert!(curve.sum(cost, 0, affordable + 1) > budget, "{:?}", curve);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use lifeclick_engine::content::registry;
+use lifeclick_engine::favor::{REPOPULATION, REPOPULATION_FAVOR};
+use lifeclick_engine::units::Souls;
+use lifeclick_engine::{GameState, Msg};
+
+/// A new game without the welcome letter, so ticks aren't held up.
+fn game() -> GameState {
+    let mut s = GameState::new();
+    s.events.clear();
+    s
+}
+
+fn has_extinction_event(s: &GameState) -> bool {
+    s.events
+        .contains_key(registry().builtin_event("extinction"))
+}
+
+#[test]
+fn deaths_never_exceed_the_living() {
+    let mut s = game();
+    s.alive = Souls::new(3);
+    s.base_death_rate = 1e6;
+    assert_eq!(s.deaths_per_tick(), Souls::new(3));
+
+    assert!(s.apply(Msg::Tick));
+    assert_eq!(s.alive, Souls::ZERO);
+    assert_eq!(s.due, Souls::new(3));
+}
+
+#[test]
+fn shrinking_populations_die_out() {
+    let mut s = game();
+    s.alive = Souls::new(500);
+    s.base_birth_rate = 2.0;
+    s.base_death_rate = 40.0;
+    assert!(s.births_per_tick() < s.deaths_per_tick());
+
+    for _ in 0..1_000 {
+        if s.is_extinct() {
+            break;
+        }
+        s.fast_forward(120);
+    }
+    assert!(s.is_extinct(), "{} still alive", s.alive);
+    assert!(has_extinction_event(&s));
+}
+
+#[test]
+fn extinct_populations_stay_extinct() {
+    let mut s = game();
+    s.alive = Souls::ZERO;
+    assert!(s.is_extinct());
+    assert_eq!(s.births_per_tick(), Souls::ZERO);
+    assert_eq!(s.deaths_per_tick(), Souls::ZERO);
+
+    s.apply(Msg::Tick);
+    assert_eq!(s.alive, Souls::ZERO);
+    assert!(!has_extinction_event(&s), "extinction announced twice");
+}
+
+#[test]
+fn dying_out_raises_the_extinction_event_once() {
+    let mut s = game();
+    s.alive = Souls::new(1);
+    s.base_death_rate = 1e6;
+
+    s.apply(Msg::Tick);
+    assert!(s.is_extinct());
+    assert!(has_extinction_event(&s));
+    assert!(s.has_active_events());
+
+    s.apply(Msg::ConsumeEvent {
+        spec: registry().builtin_event("extinction"),
+    });
+    s.apply(Msg::Tick);
+    assert!(!s.has_active_events());
+}
+
+#[test]
+fn corpses_can_be_harvested_after_extinction() {
+    let mut s = game();
+    s.alive = Souls::ZERO;
+    s.due = Souls::new(5);
+
+    assert!(s.apply(Msg::Harvest));
+    assert_eq!(s.due, Souls::new(4));
+    assert_eq!(s.souls(), Souls::new(1));
+}
+
+#[test]
+fn repopulating_takes_heaven_favor() {
+    let mut s = game();
+    s.alive = Souls::ZERO;
+    s.heaven.favor = REPOPULATION_FAVOR - Souls::new(1);
+    assert!(!s.can_repopulate());
+    assert!(!s.apply(Msg::Repopulate));
+
+    s.heaven.favor = REPOPULATION_FAVOR;
+    assert!(s.apply(Msg::Repopulate));
+    assert_eq!(s.alive, REPOPULATION);
+    assert_eq!(s.heaven.favor, Souls::ZERO);
+}
+
+#[test]
+fn repopulating_needs_an_extinction() {
+    let mut s = game();
+    s.heaven.favor = REPOPULATION_FAVOR;
+    let alive = s.alive;
+
+    assert!(!s.apply(Msg::Repopulate));
+    assert_eq!(s.alive, alive);
+    assert_eq!(s.heaven.favor, REPOPULATION_FAVOR);
+}
@@ -0,0 +1,474 @@
+use lifeclick_engine::content::registry;
+use lifeclick_engine::events::Trigger;
+use lifeclick_engine::units::Souls;
+use lifeclick_engine::{save, CustomerKind, GameState, Msg};
+
+#[test]
+fn a_short_game() {
+    let mut s = GameState::new();
+    let welcome = registry().builtin_event("welcome");
+
+    // the welcome letter holds everything up until it's read
+    assert!(!s.apply(Msg::Tick));
+    assert_eq!(s.month, 0);
+    assert!(s.apply(Msg::ConsumeEvent { spec: welcome }));
+
+    let deaths = s.deaths_per_tick();
+    assert!(s.apply(Msg::Tick));
+    assert_eq!(s.month, 1);
+    // the fraction of a death is rounded either way
+    assert!((s.due.float() - deaths.float()).abs() <= 1.0);
+    let due = s.due;
+
+    assert!(s.apply(Msg::Harvest));
+    assert_eq!(s.souls(), Souls::new(1));
+    assert_eq!(s.due, due - Souls::new(1));
+
+    let intern = registry().item("intern").unwrap();
+    s.virtuous = Souls::new(100);
+    let cost = s.items[intern].cost();
+    assert!(s.apply(Msg::Purchase {
+        spec: intern,
+        quantity: 1
+    }));
+    assert_eq!(s.item_quantity(intern), 1);
+    assert_eq!(s.souls(), Souls::new(100) - cost);
+    assert_eq!(s.souls_per_click(), Souls::new(2));
+}
+
+#[test]
+fn overflowing_months_are_called_off() {
+    let mut s = GameState::new();
+    assert!(s.apply(Msg::ConsumeEvent {
+        spec: registry().builtin_event("welcome"),
+    }));
+    s.alive = Souls::MAX;
+    s.due = Souls::MAX;
+    let before = save::to_json(&s);
+
+    // more dead than `Souls` can count
+    assert!(s.apply(Msg::Tick));
+    let overflow = registry().builtin_event("overflow");
+    let toasts: Vec<_> = s.toasts().map(|ev| ev.spec).collect();
+    assert_eq!(toasts, [overflow]);
+    assert!(s.events[overflow].details.is_some());
+
+    s.events.retain(|spec, _| *spec != overflow);
+    s.inbox.retain(|entry| entry.spec != overflow);
+    assert_eq!(save::to_json(&s), before);
+}
+
+#[test]
+fn selling_undoes_buying() {
+    let content = registry();
+    let mut s = GameState::new();
+    s.virtuous = Souls::M;
+    let (birth, death) = (s.effective_birth_rate(), s.effective_death_rate());
+
+    let survival = content.item("survival_instinct").unwrap();
+    let killer = content.item("killer_instinct").unwrap();
+    for &spec in &[survival, killer] {
+        assert!(s.apply(Msg::Purchase { spec, quantity: 5 }));
+        assert_eq!(s.item_quantity(spec), 5);
+    }
+    assert!(s.effective_birth_rate() > birth);
+    assert!(s.effective_death_rate() > death);
+
+    // the last units bought are the first sold, at their own prices
+    let before = s.souls();
+    let prices = (2..5).fold(Souls::ZERO, |total, i| total + survival.ith_cost(i));
+    assert!(s.apply(Msg::Sell {
+        spec: survival,
+        quantity: 3
+    }));
+    assert_eq!(s.item_quantity(survival), 2);
+    let refund = prices.saturating_scale(survival.refund_ratio()).trunc();
+    assert_eq!(s.souls(), before + refund);
+
+    // selling more than there is sells what there is
+    for &spec in &[survival, killer] {
+        assert!(s.apply(Msg::Sell { spec, quantity: 99 }));
+        assert_eq!(s.item_quantity(spec), 0);
+        assert!(!s.apply(Msg::Sell { spec, quantity: 1 }));
+    }
+    assert_eq!(s.effective_birth_rate(), birth);
+    assert_eq!(s.effective_death_rate(), death);
+
+    // unique items did their thing already
+    let fission = content.item("soul_fission_2").unwrap();
+    assert!(fission.unique);
+    assert!(s.apply(Msg::Purchase {
+        spec: fission,
+        quantity: 1
+    }));
+    let before = s.souls();
+    assert!(!s.apply(Msg::Sell {
+        spec: fission,
+        quantity: 1
+    }));
+    assert_eq!(s.item_quantity(fission), 1);
+    assert_eq!(s.souls(), before);
+}
+
+#[test]
+fn the_inbox_keeps_past_letters() {
+    let content = registry();
+    let mut s = GameState::with_seed(1);
+    assert!(s.apply(Msg::ConsumeEvent {
+        spec: content.builtin_event("welcome"),
+    }));
+    // a player who checks in every few years and dismisses everything
+    for _ in 0..40 {
+        s.fast_forward(50);
+        let toasts: Vec<_> = s.toasts().map(|ev| ev.spec).collect();
+        for spec in toasts {
+            assert!(s.apply(Msg::ConsumeEvent { spec }));
+        }
+    }
+    s.fast_forward(50);
+
+    let boom = content.event("baby_boom").unwrap();
+    let booms: Vec<_> = (0..s.inbox.len())
+        .filter(|&i| s.inbox[i].spec == boom)
+        .collect();
+    assert!(booms.len() > 1, "{} baby booms", booms.len());
+    assert!(booms
+        .windows(2)
+        .all(|w| s.inbox[w[0]].month < s.inbox[w[1]].month));
+    assert_eq!(s.events.values().filter(|ev| ev.spec == boom).count(), 1);
+
+    // old letters can be opened again without touching the queue
+    let queued = s.events[boom].consumed;
+    assert!(s.apply(Msg::Read {
+        entry: s.inbox[booms[0]].id
+    }));
+    assert!(s.inbox[booms[0]].read);
+    assert_eq!(s.events[boom].consumed, queued);
+
+    // opening a toast that's still up dismisses it
+    let away = content.builtin_event("away_report");
+    let latest = s.inbox.iter().rposition(|e| e.spec == away).unwrap();
+    assert!(!s.inbox[latest].read);
+    let unread = s.unread_events();
+    assert!(s.apply(Msg::Read {
+        entry: s.inbox[latest].id
+    }));
+    assert!(s.inbox[latest].read);
+    assert!(s.events[away].consumed);
+    assert_eq!(s.unread_events(), unread - 1);
+    assert!(!s.apply(Msg::Read {
+        entry: s.inbox.last().unwrap().id + 1
+    }));
+
+    let loaded = save::from_json(&save::to_json(&s)).unwrap();
+    assert_eq!(loaded.inbox.len(), s.inbox.len());
+    for (a, b) in loaded.inbox.iter().zip(&s.inbox) {
+        assert_eq!(
+            (a.id, a.spec, a.month, a.read),
+            (b.id, b.spec, b.month, b.read)
+        );
+        assert_eq!(a.details, b.details);
+    }
+}
+
+#[test]
+fn the_inbox_drops_the_oldest_letters() {
+    let mut s = GameState::new();
+    let welcome = registry().builtin_event("welcome");
+
+    // every catch-up leaves a report, and the welcome letter goes unread
+    s.fast_forward(1);
+    let first = s.inbox.last().unwrap().id;
+    for _ in 0..299 {
+        s.fast_forward(1);
+    }
+    let kept = s.inbox.len();
+    assert!(kept < 300, "{} letters", kept);
+    assert_eq!(s.inbox[0].spec, welcome);
+    assert!(!s.can_read(first));
+    assert!(!s.apply(Msg::Read { entry: first }));
+
+    let entry = &s.inbox[kept - 10];
+    let (id, month) = (entry.id, entry.month);
+    for _ in 0..30 {
+        s.fast_forward(1);
+    }
+    assert_eq!(s.inbox.len(), kept);
+    assert!(s.inbox.windows(2).all(|w| w[0].id < w[1].id));
+
+    // entries keep their number as the ones before them go
+    let loaded = save::from_json(&save::to_json(&s)).unwrap();
+    for s in &mut [s, loaded] {
+        assert!(s.apply(Msg::Read { entry: id }));
+        let entry = s.inbox.iter().find(|entry| entry.id == id).unwrap();
+        assert_eq!(entry.month, month);
+        assert!(entry.read);
+    }
+}
+
+#[test]
+fn waiting_letters_are_on_screen_already() {
+    let mut s = GameState::new();
+    let welcome = registry().builtin_event("welcome");
+    assert!(!s.can_read(0));
+    assert!(!s.apply(Msg::Read { entry: 0 }));
+
+    assert!(s.apply(Msg::ConsumeEvent { spec: welcome }));
+    assert!(s.inbox[0].read);
+    assert_eq!(s.unread_events(), 0);
+    assert!(s.can_read(0));
+}
+
+#[test]
+fn each_realm_takes_its_own_souls() {
+    let mut s = GameState::new();
+    s.virtuous = Souls::new(1_000);
+    s.sinful = Souls::new(300);
+
+    // asking for more than there is sends what there is
+    assert!(s.apply(Msg::Remit {
+        quantity: Souls::new(5_000),
+        target: CustomerKind::Heaven,
+    }));
+    assert_eq!(s.virtuous, Souls::ZERO);
+    assert_eq!(s.sinful, Souls::new(300));
+    assert_eq!(s.heaven.given, Souls::new(1_000));
+    assert_eq!(s.heaven.transactions.len(), 1);
+
+    assert!(s.apply(Msg::Remit {
+        quantity: Souls::new(100),
+        target: CustomerKind::Hell,
+    }));
+    assert_eq!(s.sinful, Souls::new(200));
+    assert_eq!(s.hell.given, Souls::new(100));
+    assert_eq!(s.heaven.given, Souls::new(1_000));
+
+    // sinful souls are no use to Heaven
+    assert!(!s.apply(Msg::Remit {
+        quantity: Souls::new(1),
+        target: CustomerKind::Heaven,
+    }));
+    assert_eq!(s.sinful, Souls::new(200));
+    assert_eq!(s.heaven.given, Souls::new(1_000));
+    assert_eq!(s.heaven.transactions.len(), 1);
+}
+
+#[test]
+fn harvests_split_by_goodness() {
+    let mut s = GameState::new();
+    s.cheat = true;
+    s.goodness = 0.75;
+    s.due = Souls::new(100);
+
+    assert!(s.apply(Msg::Harvest));
+    assert_eq!(s.due, Souls::ZERO);
+    assert_eq!(s.virtuous, Souls::new(75));
+    assert_eq!(s.sinful, Souls::new(25));
+}
+
+#[test]
+fn shopping_keeps_the_pools_in_proportion() {
+    let intern = registry().item("intern").unwrap();
+    let mut s = GameState::new();
+    s.virtuous = Souls::new(3_000_000);
+    s.sinful = Souls::new(1_000_000);
+
+    let cost = s.items[intern].cost_n(10);
+    assert!(s.apply(Msg::Purchase {
+        spec: intern,
+        quantity: 10
+    }));
+    assert_eq!(s.souls(), Souls::new(4_000_000) - cost);
+    let spent_virtuous = Souls::new(3_000_000) - s.virtuous;
+    let spent_sinful = Souls::new(1_000_000) - s.sinful;
+    assert!(spent_sinful > Souls::ZERO);
+    // 3:1, give or take the soul lost to rounding
+    let skew = spent_virtuous - spent_sinful * 3;
+    assert!(
+        skew.float().abs() <= 3.0,
+        "{} vs {}",
+        spent_virtuous,
+        spent_sinful
+    );
+
+    // a single pool pays for everything when the other is empty
+    s.sinful = Souls::ZERO;
+    let before = s.virtuous;
+    let cost = s.items[intern].cost();
+    assert!(s.apply(Msg::Purchase {
+        spec: intern,
+        quantity: 1
+    }));
+    assert_eq!(s.virtuous, before - cost);
+    assert_eq!(s.sinful, Souls::ZERO);
+}
+
+#[test]
+fn upgrades_cost_souls_from_both_pools() {
+    let paid = registry().upgrade("paid_interns").unwrap();
+    let mut s = GameState::new();
+    s.virtuous = Souls::new(3_000);
+    s.sinful = Souls::new(1_000);
+
+    assert!(s.can_buy_upgrade(paid));
+    assert!(s.apply(Msg::PurchaseUpgrade { spec: paid }));
+    assert!(s.upgrade_bought(paid));
+    assert_eq!(s.souls(), Souls::new(4_000) - paid.cost);
+    assert_eq!(s.virtuous, Souls::new(2_250));
+    assert_eq!(s.sinful, Souls::new(750));
+
+    // once is enough
+    assert!(!s.apply(Msg::PurchaseUpgrade { spec: paid }));
+    assert_eq!(s.souls(), Souls::new(4_000) - paid.cost);
+}
+
+#[test]
+fn unaffordable_upgrades_are_refused() {
+    let paid = registry().upgrade("paid_interns").unwrap();
+    let mut s = GameState::new();
+    s.virtuous = paid.cost - Souls::new(1);
+    let before = save::to_json(&s);
+
+    assert!(!s.can_buy_upgrade(paid));
+    assert!(!s.apply(Msg::PurchaseUpgrade { spec: paid }));
+    assert_eq!(save::to_json(&s), before);
+}
+
+#[test]
+fn upgrades_wait_for_their_prerequisite() {
+    let content = registry();
+    let paid = content.upgrade("paid_interns").unwrap();
+    let raise = content.upgrade("intern_raise_1").unwrap();
+    assert_eq!(raise.requires, Some(paid));
+
+    let mut s = GameState::new();
+    assert!(s.apply(Msg::ConsumeEvent {
+        spec: content.builtin_event("welcome"),
+    }));
+    s.virtuous = Souls::M;
+
+    assert!(s.apply(Msg::Tick));
+    assert!(s.upgrades[paid].revealed);
+    assert!(!s.upgrades[raise].revealed);
+    assert!(!s.can_buy_upgrade(raise));
+    let souls = s.souls();
+    assert!(!s.apply(Msg::PurchaseUpgrade { spec: raise }));
+    assert_eq!(s.souls(), souls);
+    assert!(!s.upgrade_bought(raise));
+
+    assert!(s.apply(Msg::PurchaseUpgrade { spec: paid }));
+    assert!(s.apply(Msg::Tick));
+    assert!(s.upgrades[raise].revealed);
+    assert!(s.apply(Msg::PurchaseUpgrade { spec: raise }));
+    assert!(s.upgrade_bought(raise));
+}
+
+#[test]
+fn triggered_letters_arrive_once() {
+    let content = registry();
+    let bailiff = content.item("bailiff").unwrap();
+    let hello = content.event("hello_from_hell").unwrap();
+    let letters = |s: &GameState| s.inbox.iter().filter(|e| e.spec == hello).count();
+
+    let mut s = GameState::new();
+    assert!(s.apply(Msg::ConsumeEvent {
+        spec: content.builtin_event("welcome"),
+    }));
+    s.virtuous = Souls::M;
+    assert!(s.apply(Msg::Tick));
+    assert_eq!(letters(&s), 0);
+
+    let buy = || Msg::Purchase {
+        spec: bailiff,
+        quantity: 1,
+    };
+    assert!(s.apply(buy()));
+    assert_eq!(letters(&s), 1);
+    assert!(s.apply(Msg::ConsumeEvent { spec: hello }));
+
+    assert!(s.apply(Msg::Sell {
+        spec: bailiff,
+        quantity: 1,
+    }));
+    assert!(s.apply(buy()));
+    assert!(s.apply(Msg::Tick));
+    assert_eq!(letters(&s), 1);
+    assert!(!s.has_active_events());
+}
+
+/// A game at month 24, with the census waiting for an answer.
+fn census_game() -> GameState {
+    let mut s = GameState::new();
+    assert!(s.apply(Msg::ConsumeEvent {
+        spec: registry().builtin_event("welcome"),
+    }));
+    s.month = 23;
+    assert!(s.apply(Msg::Tick));
+    assert!(s.can_choose(registry().event("census").unwrap(), 0));
+    s
+}
+
+#[test]
+fn answers_take_effect_and_are_remembered() {
+    let census = registry().event("census").unwrap();
+    let cooperated = Trigger::Chose {
+        event: census,
+        choice: "cooperate".to_owned(),
+    };
+    let mut s = census_game();
+    s.goodness = 0.5;
+    assert!(!cooperated.holds(&s));
+
+    assert!(s.apply(Msg::Choose {
+        spec: census,
+        choice: 0,
+    }));
+    assert!((s.goodness - 0.55).abs() < 1e-9);
+    assert_eq!(s.events[census].chosen.unwrap().key, "cooperate");
+    let entry = s.inbox.iter().rev().find(|e| e.spec == census).unwrap();
+    assert_eq!(entry.chosen.unwrap().key, "cooperate");
+    assert!(entry.read);
+    assert!(cooperated.holds(&s));
+
+    // one answer is all it gets
+    assert!(!s.can_choose(census, 1));
+    assert!(!s.apply(Msg::Choose {
+        spec: census,
+        choice: 1,
+    }));
+
+    let loaded = save::from_json(&save::to_json(&s)).unwrap();
+    assert_eq!(loaded.events[census].chosen.unwrap().key, "cooperate");
+    assert!(cooperated.holds(&loaded));
+}
+
+#[test]
+fn answers_can_lead_to_more_questions() {
+    let census = registry().event("census").unwrap();
+    let fine = registry().event("census_fine").unwrap();
+    let mut s = census_game();
+    assert!(!s.events.contains_key(fine));
+
+    assert!(s.apply(Msg::Choose {
+        spec: census,
+        choice: 1,
+    }));
+    assert!(s.queued_events().any(|ev| ev.spec == fine));
+
+    // paying the fine is out of reach while broke
+    let before = save::to_json(&s);
+    assert!(!s.can_choose(fine, 0));
+    assert!(!s.apply(Msg::Choose {
+        spec: fine,
+        choice: 0,
+    }));
+    assert_eq!(save::to_json(&s), before);
+
+    s.virtuous = Souls::new(800);
+    assert!(s.apply(Msg::Choose {
+        spec: fine,
+        choice: 0,
+    }));
+    assert_eq!(s.souls(), Souls::new(300));
+    assert!(!s.has_active_events());
+}
@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+
+use lifeclick_engine::content::{registry, ContentError, Registry};
+use lifeclick_engine::GameState;
+
+fn assert_unique<'a>(kind: &str, keys: impl Iterator<Item = &'a String>) {
+    let mut seen = HashSet::new();
+    for key in keys {
+        assert!(!key.is_empty(), "{} with an empty key", kind);
+        assert!(seen.insert(key), "{} `{}` registered twice", kind, key);
+    }
+}
+
+#[test]
+fn keys_are_unique() {
+    let content = registry();
+    assert_unique("item", content.items.iter().map(|spec| &spec.key));
+    assert_unique("upgrade", content.upgrades.iter().map(|spec| &spec.key));
+    assert_unique("event", content.events.iter().map(|spec| &spec.key));
+}
+
+#[test]
+fn lookups_find_the_registered_spec() {
+    let content = registry();
+    for &spec in &content.items {
+        assert!(std::ptr::eq(content.item(&spec.key).unwrap(), spec));
+    }
+    for &spec in &content.upgrades {
+        assert!(std::ptr::eq(content.upgrade(&spec.key).unwrap(), spec));
+    }
+    for &spec in &content.events {
+        assert!(std::ptr::eq(content.event(&spec.key).unwrap(), spec));
+    }
+}
+
+#[test]
+fn new_game_has_every_item_and_upgrade_once() {
+    let content = registry();
+    let state = GameState::new();
+
+    assert_eq!(state.items.len(), content.items.len());
+    for &spec in &content.items {
+        let item = state.items.get(spec).expect("item missing from new game");
+        assert!(std::ptr::eq(item.spec, spec));
+    }
+
+    assert_eq!(state.upgrades.len(), content.upgrades.len());
+    for &spec in &content.upgrades {
+        let up = state
+            .upgrades
+            .get(spec)
+            .expect("upgrade missing from new game");
+        assert!(std::ptr::eq(up.spec, spec));
+    }
+}
+
+#[test]
+fn duplicate_keys_are_rejected() {
+    let items = r#"[(key: "intern", name: "Intern"), (key: "intern", name: "Intern")]"#;
+    match Registry::parse(items, "[]", "[]") {
+        Err(ContentError::DuplicateKey { file, key }) => {
+            assert_eq!(file, "items.ron");
+            assert_eq!(key, "intern");
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("duplicate key accepted"),
+    }
+}
+
+#[test]
+fn unknown_trigger_keys_are_rejected() {
+    let events =
+        r#"[(key: "oops", name: "Oops", triggers: [ItemQuantity(item: "nope", quantity: 1)])]"#;
+    match Registry::parse("[]", "[]", events) {
+        Err(ContentError::UnknownTrigger { event, key }) => {
+            assert_eq!(event, "oops");
+            assert_eq!(key, "nope");
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("unknown trigger accepted"),
+    }
+}
+
+#[test]
+fn choices_must_be_answerable_once_for_all() {
+    let costly = r#"[(key: "toll", name: "Toll", choices: [
+        (key: "pay", label: "Pay", effects: [Souls(-10)]),
+        (key: "pay_more", label: "Pay more", effects: [Souls(-20), Goodness(0.1)]),
+    ])]"#;
+    match Registry::parse("[]", "[]", costly) {
+        Err(ContentError::NoFreeChoice { event }) => assert_eq!(event, "toll"),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("event without a free choice accepted"),
+    }
+
+    // paying first still takes souls, whatever comes back after
+    let refund = r#"[(key: "deposit", name: "Deposit", choices: [
+        (key: "pay", label: "Pay", effects: [Souls(-500), Souls(500)]),
+    ])]"#;
+    match Registry::parse("[]", "[]", refund) {
+        Err(ContentError::NoFreeChoice { event }) => assert_eq!(event, "deposit"),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("event without a free choice accepted"),
+    }
+
+    let random = r#"[(key: "poll", name: "Poll", chance: 0.1, choices: [
+        (key: "yes", label: "Yes"),
+        (key: "no", label: "No"),
+    ])]"#;
+    match Registry::parse("[]", "[]", random) {
+        Err(ContentError::RandomWithChoices { event }) => assert_eq!(event, "poll"),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("random event with choices accepted"),
+    }
+}
+
+#[test]
+fn only_choices_take_souls() {
+    let events = r#"[(key: "tax", name: "Tax", triggers: [Month(12)], effects: [Souls(-100)])]"#;
+    match Registry::parse("[]", "[]", events) {
+        Err(ContentError::CostlyEffect { event }) => assert_eq!(event, "tax"),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("event taking souls by itself accepted"),
+    }
+}
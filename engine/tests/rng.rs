@@ -0,0 +1,67 @@
+use lifeclick_engine::content::registry;
+use lifeclick_engine::rng::Rng;
+use lifeclick_engine::{save, GameState};
+
+/// A game that plays by itself for `months`, events and all.
+fn play(seed: u64, months: i64) -> GameState {
+    let mut s = GameState::with_seed(seed);
+    s.fast_forward(months);
+    s
+}
+
+#[test]
+fn same_seed_same_numbers() {
+    let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+    for _ in 0..100 {
+        assert_eq!(a.next_u32(), b.next_u32());
+    }
+    assert_ne!(Rng::new(7).next_u32(), Rng::new(8).next_u32());
+}
+
+#[test]
+fn floats_stay_in_range() {
+    let mut rng = Rng::new(1);
+    for _ in 0..10_000 {
+        let x = rng.next_f64();
+        assert!((0.0..1.0).contains(&x), "{} out of range", x);
+    }
+}
+
+#[test]
+fn loading_a_save_doesnt_reroll() {
+    let mut straight = play(42, 400);
+
+    let halfway = play(42, 200);
+    let mut resumed = save::from_json(&save::to_json(&halfway)).unwrap();
+    resumed.fast_forward(200);
+
+    // the only thing allowed to differ: how long each catch-up was, and
+    // so how the letters after the reports are numbered
+    for s in [&mut straight, &mut resumed] {
+        s.events.retain(|spec, _| spec.key != "away_report");
+        s.inbox.retain(|entry| entry.spec.key != "away_report");
+        for (id, entry) in s.inbox.iter_mut().enumerate() {
+            entry.id = id as u64;
+        }
+    }
+    assert_eq!(save::to_json(&straight), save::to_json(&resumed));
+}
+
+#[test]
+fn toasts_keep_coming_unread() {
+    let boom = registry().event("baby_boom").unwrap();
+    let mut s = GameState::with_seed(3);
+    let (mut booms, mut seen) = (0, 0);
+    // nobody's there to dismiss anything, for a long while
+    for _ in 0..100 {
+        s.fast_forward(100);
+        booms += s
+            .inbox
+            .iter()
+            .filter(|entry| entry.spec == boom && entry.id >= seen)
+            .count();
+        seen = s.inbox.last().unwrap().id + 1;
+    }
+    // 2% a month for 10,000 months
+    assert!((150..250).contains(&booms), "{} baby booms", booms);
+}
@@ -0,0 +1,153 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use lifeclick_engine::content::registry;
+use lifeclick_engine::events::Details;
+use lifeclick_engine::save::{self, LoadError, SAVE_VERSION};
+use lifeclick_engine::units::Souls;
+use lifeclick_engine::GameState;
+
+/// A save from the very first release, before migrations existed.
+const V1_SAVE: &str = r#"{
+    "version": 1,
+    "month": 120,
+    "alive": 900000,
+    "due": 42,
+    "souls": 1000,
+    "base_birth_rate": 6.0,
+    "base_death_rate": 4.0,
+    "goodness": 0.75,
+    "heaven": { "given": 2000 },
+    "hell": { "given": 30 },
+    "items": [
+        { "key": "intern", "quantity": 4, "revealed": true },
+        { "key": "plague_small", "quantity": 3, "revealed": true },
+        { "key": "long_gone", "quantity": 7, "revealed": true }
+    ],
+    "upgrades": [
+        { "key": "paid_interns", "revealed": true, "bought": true }
+    ],
+    "events": [
+        { "key": "welcome", "consumed": true }
+    ]
+}"#;
+
+#[test]
+fn v1_saves_still_load() {
+    let content = registry();
+    let s = save::from_json(V1_SAVE).expect("v1 save should load");
+
+    assert_eq!(s.month, 120);
+    assert_eq!(s.alive, Souls::new(900_000));
+    assert_eq!(s.due, Souls::new(42));
+
+    // v5: souls split by goodness
+    assert_eq!(s.virtuous, Souls::new(750));
+    assert_eq!(s.sinful, Souls::new(250));
+
+    // v4: everything given so far counts as favor
+    assert_eq!(s.heaven.favor, Souls::new(2000));
+    assert_eq!(s.hell.favor, Souls::new(30));
+    assert!(s.heaven.transactions.is_empty());
+
+    let intern = content.item("intern").unwrap();
+    assert_eq!(s.item_quantity(intern), 4);
+    // unique items used to be buyable more than once
+    assert_eq!(s.item_quantity(content.item("plague_small").unwrap()), 1);
+
+    let paid = content.upgrade("paid_interns").unwrap();
+    assert!(s.upgrade_bought(paid));
+    assert_eq!(intern.get_spc(&s).unwrap().bonus, 1.5);
+
+    let welcome = content.builtin_event("welcome");
+    assert!(s.events[welcome].consumed);
+    assert!(!s.has_active_events());
+
+    // v11: the inbox starts out as the events saved so far
+    assert_eq!(s.inbox.len(), 1);
+    assert_eq!(s.inbox[0].spec, welcome);
+    assert!(s.inbox[0].read);
+    // v12: and numbered from the start
+    assert_eq!(s.inbox[0].id, 0);
+}
+
+/// Decodes an export, lets `f` tamper with the bytes, and encodes it back.
+fn tampered(f: impl FnOnce(&mut Vec<u8>)) -> String {
+    let mut data = STANDARD.decode(save::export(&GameState::new())).unwrap();
+    f(&mut data);
+    STANDARD.encode(&data)
+}
+
+#[test]
+fn exports_round_trip() {
+    let mut s = GameState::new();
+    s.month = 77;
+    s.virtuous = Souls::new(12_345);
+    let exported = save::export(&s);
+
+    // pasting tends to add line breaks
+    let wrapped = format!(" {}\n{} ", &exported[..10], &exported[10..]);
+    let loaded = save::import(&wrapped).expect("export should import");
+    assert_eq!(loaded.month, 77);
+    assert_eq!(loaded.virtuous, Souls::new(12_345));
+}
+
+#[test]
+fn away_reports_keep_their_numbers() {
+    let mut s = GameState::new();
+    let report = s.fast_forward(24);
+    let loaded = save::from_json(&save::to_json(&s)).unwrap();
+    let away = registry().builtin_event("away_report");
+    assert_eq!(loaded.events[away].details, Some(Details::Away(report)));
+}
+
+#[test]
+fn empty_imports_are_reported_as_such() {
+    assert!(matches!(save::import(""), Err(LoadError::Empty)));
+    assert!(matches!(save::import("  \n "), Err(LoadError::Empty)));
+}
+
+#[test]
+fn truncated_imports_are_reported_as_such() {
+    let exported = save::export(&GameState::new());
+    for len in [3, 12, exported.len() / 2, exported.len() - 5] {
+        match save::import(&exported[..len]) {
+            Err(LoadError::Truncated) => {}
+            other => panic!("cut at {}: {:?}", len, other.err()),
+        }
+    }
+}
+
+#[test]
+fn garbage_is_corrupt() {
+    for garbage in [
+        "hello world",
+        "!!!!",
+        "SGVsbG8gd29ybGQsIGhvdyBhcmUgeW91Pw==",
+    ] {
+        match save::import(garbage) {
+            Err(LoadError::Corrupt(_)) => {}
+            other => panic!("{:?}: {:?}", garbage, other.err()),
+        }
+    }
+}
+
+#[test]
+fn checksum_mismatches_are_corrupt() {
+    let input = tampered(|data| data[8] ^= 0xff);
+    match save::import(&input) {
+        Err(LoadError::Corrupt(reason)) => assert!(reason.contains("checksum")),
+        other => panic!("{:?}", other.err()),
+    }
+}
+
+#[test]
+fn newer_saves_are_refused() {
+    let input = tampered(|data| data[4..8].copy_from_slice(&(SAVE_VERSION + 1).to_le_bytes()));
+    match save::import(&input) {
+        Err(LoadError::NewerVersion { found, supported }) => {
+            assert_eq!(found, SAVE_VERSION + 1);
+            assert_eq!(supported, SAVE_VERSION);
+        }
+        other => panic!("{:?}", other.err()),
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+
+use lifeclick_engine::units::{Locale, Notation, NumberFormat, Souls};
+
+/// 1e15 - 1, the biggest amount stored exactly.
+const BIGGEST_EXACT: i64 = 999_999_999_999_999;
+
+fn ten_to(n: i64) -> Souls {
+    Souls::new(1).times_pow(10.0, n as f64)
+}
+
+#[test]
+fn equal_amounts_look_the_same() {
+    let ways = [
+        Souls::T.checked_mul(Souls::T).unwrap(),
+        ten_to(24),
+        Souls::from_f64(1e24),
+        (Souls::M * 1_000_000)
+            .checked_mul(Souls::M * 1_000_000)
+            .unwrap(),
+    ];
+    let json = serde_json::to_string(&ways[0]).unwrap();
+    for souls in &ways {
+        assert_eq!(*souls, ways[0]);
+        assert_eq!(serde_json::to_string(souls).unwrap(), json);
+    }
+
+    let zeroes = [
+        Souls::ZERO,
+        -Souls::ZERO,
+        Souls::new(5) - Souls::new(5),
+        Souls::new(-3).checked_scale(0.0).unwrap(),
+        Souls::new(7).checked_scale(0.01).unwrap().trunc(),
+    ];
+    for souls in &zeroes {
+        assert_eq!(*souls, Souls::ZERO);
+        assert!(souls.is_zero());
+        assert!(!souls.is_negative());
+        assert_eq!(serde_json::to_string(souls).unwrap(), "0");
+    }
+}
+
+#[test]
+fn ordering_holds_across_the_exact_limit() {
+    let below = Souls::new(BIGGEST_EXACT);
+    let limit = Souls::new(BIGGEST_EXACT + 1);
+    assert!(below < limit);
+    assert!(limit > below);
+    assert_eq!(below + Souls::new(1), limit);
+    assert_eq!(limit - Souls::new(1), below);
+    assert!(-below > -limit);
+    assert!(Souls::new(-1) < below);
+    assert!(ten_to(16) > limit);
+    assert!(Souls::MAX > ten_to(1_000_000));
+    assert!(Souls::MIN < -ten_to(1_000_000));
+}
+
+#[test]
+fn equal_amounts_hash_the_same() {
+    let mut set = HashSet::new();
+    set.insert(Souls::new(BIGGEST_EXACT + 1));
+    set.insert(Souls::from_f64(1e15));
+    set.insert(ten_to(15));
+    set.insert(Souls::new(BIGGEST_EXACT) + Souls::new(1));
+    set.insert(Souls::B.checked_mul(Souls::M).unwrap());
+    assert_eq!(set.len(), 1);
+
+    set.clear();
+    set.insert(Souls::ZERO);
+    set.insert(-Souls::ZERO);
+    set.insert(Souls::K - Souls::K);
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn adding_far_apart_amounts() {
+    let huge = ten_to(40);
+    assert_eq!(huge.checked_add(Souls::new(1)), Some(huge));
+    assert_eq!(huge.checked_sub(Souls::new(1)), Some(huge));
+    assert_eq!(Souls::new(1).checked_add(huge), Some(huge));
+    assert_eq!(Souls::new(1).checked_sub(huge), Some(-huge));
+
+    // close enough that the small one still counts
+    let big = ten_to(20);
+    let sum = big.checked_add(Souls::M).unwrap();
+    assert!(sum > big);
+    assert_eq!(sum.checked_sub(big), Some(Souls::M));
+
+    assert_eq!(Souls::MAX.checked_add(Souls::new(1)), Some(Souls::MAX));
+    assert_eq!(Souls::MAX.checked_add(Souls::MAX), None);
+    assert_eq!(Souls::MIN.checked_sub(Souls::MAX), None);
+    assert_eq!(Souls::MAX.saturating_add(Souls::MAX), Souls::MAX);
+    assert_eq!(Souls::MIN.saturating_sub(Souls::MAX), Souls::MIN);
+}
+
+#[test]
+fn serde_round_trips() {
+    let amounts = [
+        Souls::ZERO,
+        Souls::new(42),
+        Souls::new(-42),
+        Souls::new(BIGGEST_EXACT),
+        Souls::new(-BIGGEST_EXACT),
+        Souls::new(BIGGEST_EXACT + 1),
+        ten_to(100).saturating_times(123),
+        -ten_to(100),
+        Souls::MAX,
+        Souls::MIN,
+    ];
+    for &souls in &amounts {
+        let json = serde_json::to_string(&souls).unwrap();
+        let back: Souls = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, souls, "{}", json);
+    }
+
+    assert_eq!(serde_json::to_string(&Souls::new(42)).unwrap(), "42");
+    assert_eq!(
+        serde_json::to_string(&Souls::new(BIGGEST_EXACT)).unwrap(),
+        BIGGEST_EXACT.to_string()
+    );
+    let limit = serde_json::to_string(&Souls::new(BIGGEST_EXACT + 1)).unwrap();
+    assert!(limit.starts_with('['), "{}", limit);
+}
+
+#[test]
+fn serde_accepts_every_form() {
+    let parse = |json: &str| serde_json::from_str::<Souls>(json);
+    assert_eq!(parse("1000").unwrap(), Souls::K);
+    assert_eq!(parse("12.7").unwrap(), Souls::new(12));
+    assert_eq!(parse("[1.5, 3]").unwrap(), Souls::new(1_500));
+    assert_eq!(parse("[1.0, 24]").unwrap(), ten_to(24));
+    assert_eq!(parse("[10.0, 23]").unwrap(), ten_to(24));
+    assert!(parse("[1.0, 4611686018427387904]").is_err());
+    assert!(parse("\"lots\"").is_err());
+}
+
+fn show(souls: Souls, notation: Notation, locale: Locale) -> String {
+    souls.display(NumberFormat { notation, locale }).to_string()
+}
+
+#[test]
+fn rounding_carries_into_the_next_suffix() {
+    let short = |n: i64| show(Souls::new(n), Notation::Short, Locale::en);
+    assert_eq!(short(999_994_999), "999.99 M");
+    assert_eq!(short(999_995_000), "1.00 B");
+    assert_eq!(short(-999_995_000), "-1.00 B");
+    assert_eq!(
+        show(Souls::new(999_995_000), Notation::Long, Locale::en),
+        "1.00 billion"
+    );
+    assert_eq!(
+        show(Souls::new(999_995), Notation::Scientific, Locale::en),
+        "999,995"
+    );
+    assert_eq!(
+        show(Souls::new(9_999_950), Notation::Scientific, Locale::en),
+        "1.00e7"
+    );
+}
+
+#[test]
+fn suffixes_give_way_to_scientific() {
+    let short = |souls: Souls| show(souls, Notation::Short, Locale::en);
+    assert_eq!(short(ten_to(33)), "1.00 Dc");
+    assert_eq!(short(ten_to(35).saturating_times(9)), "900.00 Dc");
+    assert_eq!(short(ten_to(36)), "1.00e36");
+    assert_eq!(short(ten_to(33).saturating_times(999_995)), "1.00e39");
+    assert_eq!(short(ten_to(30).saturating_times(999_995)), "1.00e36");
+    assert_eq!(show(ten_to(36), Notation::Long, Locale::en), "1.00e36");
+}
+
+#[test]
+fn locales_pick_the_separators() {
+    let de = |souls: Souls, notation| show(souls, notation, Locale::de);
+    assert_eq!(de(Souls::new(123_456), Notation::Short), "123.456");
+    assert_eq!(de(Souls::new(1_234_567_890), Notation::Short), "1,23 B");
+    assert_eq!(
+        de(Souls::new(1_234_567_890), Notation::Full),
+        "1.234.567.890"
+    );
+    assert_eq!(
+        de(Souls::new(1_234_567_890), Notation::Scientific),
+        "1,23e9"
+    );
+    assert_eq!(
+        de(Souls::new(-1_234_567_890), Notation::Engineering),
+        "-1,23e9"
+    );
+    assert_eq!(
+        show(Souls::new(1_234_567_890), Notation::Short, Locale::en),
+        "1.23 B"
+    );
+}
@@ -1,8 +0,0 @@
-
-pub fn idgen() -> i64 {
-    static mut SEED: i64 = 0;
-    unsafe {
-        SEED += 1;
-        SEED
-    }
-}
\ No newline at end of file
@@ -2,45 +2,43 @@
 
 use std::cmp;
 use std::time::Duration;
+use stdweb::web::Date;
 use stdweb::*;
+use yew::events::ChangeData;
+use yew::format::Text;
+use yew::services::storage::{Area, StorageService};
 use yew::services::{IntervalService, Task};
 use yew::virtual_dom::vlist::VList;
 use yew::virtual_dom::vnode::VNode;
 use yew::{html, Component, ComponentLink, Html, Renderable, ShouldRender};
 
-use indexmap::IndexMap;
-
-mod idgen;
-
-mod units;
-use units::*;
-
-mod items;
-use items::{Item, ItemCategory, ItemSpec};
-
-mod upgrades;
-use upgrades::{Upgrade, UpgradeEffect, UpgradeSpec};
-
-mod events;
-use events::{Event, EventSpec};
-
-// ok, ok, I get it
-const DAYS_PER_YEAR: f64 = 365.25;
-const DAYS_PER_TICK: f64 = 31.0;
-const TICK_UNIT: &str = "month";
-
-pub struct Customer {
-    kind: CustomerKind,
-    name: String,
-    sign: String,
-    given: Souls,
-}
-
-#[derive(Clone, Copy)]
-pub enum CustomerKind {
-    Heaven,
-    Hell,
-}
+use lifeclick_engine::events::{Details, Event, EventSpec, InboxEntry};
+use lifeclick_engine::favor;
+use lifeclick_engine::items::{Item, ItemCategory};
+use lifeclick_engine::save;
+use lifeclick_engine::units::*;
+use lifeclick_engine::upgrades::Upgrade;
+use lifeclick_engine::{Customer, CustomerKind, GameState, Modifier, Msg as GameMsg, TICK_UNIT};
+
+const SAVE_KEY: &str = "lifeclick.save";
+const BAD_SAVE_KEY: &str = "lifeclick.save.bad";
+/// Display preferences aren't part of the game, so they live apart from
+/// saves, as `"<notation> <locale>"`.
+const FORMAT_KEY: &str = "lifeclick.format";
+const AUTOSAVE_SECS: u64 = 10;
+/// Don't simulate more than this many months of offline progress.
+const MAX_OFFLINE_MONTHS: i64 = 12_000;
+/// Locales offered in the settings.
+const LOCALES: &[Locale] = &[
+    Locale::en,
+    Locale::en_IN,
+    Locale::de,
+    Locale::de_CH,
+    Locale::es,
+    Locale::fr,
+    Locale::it,
+    Locale::pt,
+];
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Tab {
@@ -48,6 +46,8 @@ pub enum Tab {
     Earth,
     Heaven,
     Hell,
+    Inbox,
+    Settings,
 }
 
 macro_rules! empty {
@@ -57,12 +57,12 @@ macro_rules! empty {
 }
 
 macro_rules! delta {
-    ($q:expr) => {{
+    ($q:expr, $format:expr) => {{
         let q = $q;
         if q >= 0.into() {
-            format!("+{}", q)
+            format!("+{}", q.display($format))
         } else {
-            format!("{}", q)
+            format!("{}", q.display($format))
         }
     }};
 }
@@ -79,59 +79,38 @@ pub struct Model {
     #[allow(dead_code)]
     interval: IntervalService,
     #[allow(dead_code)]
-    job: Option<Box<Task>>,
-
-    alive: Souls,
-    due: Souls,
-    souls: Souls,
-
-    base_birth_rate: f64,
-    base_death_rate: f64,
-
-    goodness: f64,
-
-    month: i64,
-
-    heaven: Customer,
-    hell: Customer,
+    job: Option<Box<dyn Task>>,
+    #[allow(dead_code)]
+    autosave_job: Option<Box<dyn Task>>,
+    storage: StorageService,
 
-    items: IndexMap<&'static ItemSpec, Item>,
-    effects: IndexMap<&'static ItemSpec, Vec<&'static UpgradeEffect>>,
-    upgrades: IndexMap<&'static UpgradeSpec, Upgrade>,
-    events: IndexMap<&'static EventSpec, Event>,
+    state: GameState,
+    format: NumberFormat,
 
     tab: Tab,
     item_category: ItemCategory,
+    /// Inbox entry being re-read, by `InboxEntry::id`.
+    reading: Option<u64>,
 
-    cheat: bool,
+    exported: Option<String>,
+    import_text: String,
+    import_error: Option<String>,
 }
 
 pub enum Msg {
-    Tick,
-    Remit {
-        quantity: Souls,
-        target: CustomerKind,
-    },
-    Harvest,
-    Purchase {
-        spec: &'static ItemSpec,
-        quantity: i64,
-    },
-    FocusTab {
-        tab: Tab,
-    },
-    FocusItemCategory {
-        category: ItemCategory,
-    },
-    ConsumeEvent {
-        spec: &'static EventSpec,
-    },
-    PurchaseUpgrade {
-        spec: &'static UpgradeSpec,
-    },
+    Game(GameMsg),
+    Save,
+    ExportSave,
+    UpdateImport(String),
+    ImportSave,
+    SelectNotation(String),
+    SelectLocale(String),
+    FocusTab { tab: Tab },
+    FocusItemCategory { category: ItemCategory },
+    OpenEvent { entry: u64 },
+    CloseEvent,
 }
 
-#[allow(dead_code)]
 fn log(msg: &str) {
     js! { console.log(@{msg}) }
 }
@@ -144,109 +123,95 @@ impl Component for Model {
 
     fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
         let mut interval = IntervalService::new();
-        let millis: u64 = if cheat_enabled() { 50 } else { 100 };
-        let handle = interval.spawn(Duration::from_millis(millis), link.send_back(|_| Msg::Tick));
+        let handle = interval.spawn(
+            Duration::from_millis(tick_millis()),
+            link.send_back(|_| Msg::Game(GameMsg::Tick)),
+        );
+        let autosave_handle = interval.spawn(
+            Duration::from_secs(AUTOSAVE_SECS),
+            link.send_back(|_| Msg::Save),
+        );
+
+        let on_unload = link.send_back(|_: ()| Msg::Save);
+        let save_on_unload = move || on_unload.emit(());
+        js! { @(no_return)
+            var save = @{save_on_unload};
+            window.addEventListener("beforeunload", function() { save(); });
+        }
+
+        let mut storage = StorageService::new(Area::Local);
+        let mut state = restore(&mut storage);
+        state.cheat = cheat_enabled();
+        catch_up(&mut state);
+        let format = restore_format(&mut storage);
 
-        let mut m = Model {
+        Model {
             interval,
             job: Some(Box::new(handle)),
+            autosave_job: Some(Box::new(autosave_handle)),
+            storage,
 
-            due: Souls(0),
-            souls: Souls(0),
-
-            month: 0,
-
-            // 2019 stats:
-            // birth_rate: 18.5,
-            // death_rate: 7.8,
-            // alive: 7 * Souls::B,
-
-            // Better starting point:
-            base_birth_rate: 6.0,
-            base_death_rate: 4.0,
-            alive: 800 * Souls::K,
-
-            goodness: 1.0,
-
-            heaven: Customer {
-                kind: CustomerKind::Heaven,
-                name: "Heaven".to_owned(),
-                sign: "✝️".to_owned(),
-                given: Souls(0),
-            },
-            hell: Customer {
-                kind: CustomerKind::Hell,
-                name: "Hell".to_owned(),
-                sign: "⛧️".to_owned(),
-                given: Souls(0),
-            },
-
-            items: IndexMap::new(),
-            effects: IndexMap::new(),
-            events: IndexMap::new(),
-            upgrades: IndexMap::new(),
+            state,
+            format,
 
             tab: Tab::Shop,
             item_category: ItemCategory::Harvest,
+            reading: None,
 
-            cheat: cheat_enabled(),
-        };
-
-        // items
-        m.add_item(&items::Intern, 0);
-        m.add_item(&items::Bailiff, 0);
-        m.add_item(&items::Banker, 0);
-        m.add_item(&items::Accountant, 0);
-        m.add_item(&items::CollectionAgency, 0);
-        m.add_item(&items::CollectionMultinational, 0);
-        m.add_item(&items::SurvivalInstinct, 0);
-        m.add_item(&items::KillerInstinct, 0);
-        m.add_item(&items::SoulFission, 0);
-        m.add_item(&items::PlagueSmall, 0);
-
-        // upgrades
-        m.add_upgrade(&upgrades::PaidInterns);
-        m.add_upgrade(&upgrades::InternRaise1);
-        m.add_upgrade(&upgrades::ArmedBailiffs);
-
-        // events
-        m.add_event(&events::Welcome);
-
-        m
+            exported: None,
+            import_text: String::new(),
+            import_error: None,
+        }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::Harvest => {
-                self.harvest(self.souls_per_click());
+            Msg::Game(msg) => self.state.apply(msg),
+            Msg::Save => {
+                self.state.saved_at = Some(now());
+                let data: Text = Ok(save::to_json(&self.state));
+                self.storage.store(SAVE_KEY, data);
+                false
+            }
+            Msg::ExportSave => {
+                self.exported = Some(save::export(&self.state));
                 true
             }
-            Msg::Remit { quantity, target } => {
-                let remitted = cmp::min(self.souls, quantity);
-                {
-                    let cus = self.customer_mut(target);
-                    cus.given += remitted;
-                }
-                self.souls -= remitted;
+            Msg::UpdateImport(text) => {
+                self.import_text = text;
+                self.import_error = None;
                 true
             }
-            Msg::Purchase { quantity, spec } => {
-                for _i in 0..quantity {
-                    let new_quantity = {
-                        let item = self.items.get_mut(spec).unwrap();
-                        let cost = item.cost();
-
-                        if cost > self.souls {
-                            break;
-                        }
-                        self.souls -= cost;
-                        item.quantity += 1;
-                        item.quantity
-                    };
-                    self.apply_buy_effects(spec, new_quantity);
+            Msg::ImportSave => {
+                match save::import(&self.import_text) {
+                    Ok(mut state) => {
+                        state.cheat = self.state.cheat;
+                        self.state = state;
+                        self.import_text.clear();
+                        self.import_error = None;
+                        self.exported = None;
+                        self.update(Msg::Save);
+                    }
+                    Err(e) => self.import_error = Some(format!("Could not import save: {}", e)),
                 }
                 true
             }
+            Msg::SelectNotation(key) => match Notation::from_key(&key) {
+                Some(notation) => {
+                    self.format.notation = notation;
+                    self.store_format();
+                    true
+                }
+                None => false,
+            },
+            Msg::SelectLocale(name) => match Locale::from_name(&name) {
+                Ok(locale) => {
+                    self.format.locale = locale;
+                    self.store_format();
+                    true
+                }
+                Err(_) => false,
+            },
             Msg::FocusTab { tab } => {
                 self.tab = tab;
                 true
@@ -255,47 +220,17 @@ impl Component for Model {
                 self.item_category = category;
                 true
             }
-            Msg::Tick => {
-                if self.has_active_events() {
+            Msg::OpenEvent { entry } => {
+                if !self.state.apply(GameMsg::Read { entry }) {
                     return false;
                 }
-
-                let deaths = self.deaths_per_tick();
-
-                self.due += deaths;
-                self.alive -= deaths;
-
-                let births = self.births_per_tick();
-                self.alive += births;
-
-                self.month += 1;
-
-                self.harvest(self.souls_per_tick());
-                self.update_items_reveal();
-                self.update_upgrades_reveal();
-
+                self.reading = Some(entry);
                 true
             }
-            Msg::ConsumeEvent { spec } => {
-                if let Some(ev) = self.events.get_mut(spec) {
-                    ev.consumed = true;
-                }
+            Msg::CloseEvent => {
+                self.reading = None;
                 true
             }
-            Msg::PurchaseUpgrade { spec } => {
-                let mut apply = false;
-                if let Some(up) = self.upgrades.get_mut(spec) {
-                    if !up.bought {
-                        up.bought = true;
-                        apply = true;
-                    }
-                }
-
-                if apply {
-                    self.apply_upgrade(spec);
-                }
-                apply
-            }
         }
     }
 }
@@ -324,35 +259,18 @@ impl Renderable<Model> for Model {
 }
 
 impl Model {
-    fn add_item(&mut self, spec: &'static ItemSpec, quantity: i64) {
-        let item = spec.instantiate(quantity);
-        self.items.insert(item.spec, item);
+    /// Plain numbers, written the same way as amounts of souls.
+    fn count(&self, n: i64) -> Formatted {
+        Souls::new(n).display(self.format)
     }
 
-    fn add_upgrade(&mut self, spec: &'static UpgradeSpec) {
-        let upgrade = spec.instantiate();
-        self.upgrades.insert(upgrade.spec, upgrade);
-    }
-
-    fn add_event(&mut self, spec: &'static EventSpec) {
-        let event = spec.instantiate();
-        self.events.insert(event.spec, event);
-    }
-
-    #[allow(dead_code)]
-    fn customer<'a>(&'a self, kind: CustomerKind) -> &'a Customer {
-        match kind {
-            CustomerKind::Heaven => &self.heaven,
-            CustomerKind::Hell => &self.hell,
-        }
-    }
-
-    #[allow(dead_code)]
-    fn customer_mut<'a>(&'a mut self, kind: CustomerKind) -> &'a mut Customer {
-        match kind {
-            CustomerKind::Heaven => &mut self.heaven,
-            CustomerKind::Hell => &mut self.hell,
-        }
+    fn store_format(&mut self) {
+        let data = format!(
+            "{} {}",
+            self.format.notation.key(),
+            self.format.locale.name()
+        );
+        self.storage.store(FORMAT_KEY, Ok(data) as Text);
     }
 
     fn prelude(&self) -> Html<Self> {
@@ -376,26 +294,117 @@ impl Model {
                         </strong>
                     </p>
                     <p>
-                        { format!(" You've given {} {} souls.", customer.name, customer.given) }
+                        { format!(" You've given {} {} souls.", customer.name, customer.given.display(self.format)) }
+                    </p>
+                    <p>
+                        { match kind {
+                            CustomerKind::Heaven => "Heaven only accepts virtuous souls.",
+                            CustomerKind::Hell => "Hell only accepts sinful souls.",
+                        } }
                     </p>
                 </div>
                 { self.render_remit_bar(kind) }
+                { self.render_favor(customer) }
+                { self.render_transactions(customer) }
             </>
         }
     }
 
+    fn render_favor(&self, customer: &Customer) -> Html<Self> {
+        let current = favor::tier(customer.favor);
+
+        html! {
+            <div class="content", style="margin-top: 1.5em",>
+                <p>
+                    { format!("Standing: {} ({} favor)", current.name, customer.favor.display(self.format)) }
+                    { if let Some(next) = favor::next_tier(customer.favor) {
+                        html! {
+                            { format!(" - {} at {}", next.name, next.min.display(self.format)) }
+                        }
+                    } else { empty!() } }
+                </p>
+                <ul>
+                    {for (1..favor::TIERS.len()).map(|i| self.render_favor_tier(customer, i))}
+                </ul>
+            </div>
+        }
+    }
+
+    fn render_favor_tier(&self, customer: &Customer, i: usize) -> Html<Self> {
+        let tier = &favor::TIERS[i];
+        let upper = favor::TIERS.get(i + 1).map(|next| next.min);
+        let unlocks: Vec<&str> = self
+            .state
+            .items
+            .keys()
+            .filter(|spec| match spec.min_favor(customer.kind) {
+                Some(min) => min >= tier.min && upper.map(|upper| min < upper).unwrap_or(true),
+                None => false,
+            })
+            .map(|spec| spec.name.as_str())
+            .collect();
+        let class = if customer.favor >= tier.min {
+            "has-text-success"
+        } else {
+            "has-text-grey"
+        };
+
+        html! {
+            <li class=class,>
+                { format!("{} ({}): ", tier.name, tier.min.display(self.format)) }
+                { if unlocks.is_empty() {
+                    "nothing yet".to_owned()
+                } else {
+                    unlocks.join(", ")
+                } }
+            </li>
+        }
+    }
+
+    fn render_transactions(&self, customer: &Customer) -> Html<Self> {
+        if customer.transactions.is_empty() {
+            return empty!();
+        }
+
+        html! {
+            <table class="table is-fullwidth is-narrow",>
+                <thead>
+                    <tr>
+                        <th>{ "Month" }</th>
+                        <th>{ "Remitted" }</th>
+                        <th>{ "Credited" }</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {for customer.transactions.iter().rev().map(|tx| {
+                        html! {
+                            <tr>
+                                <td>{ tx.month }</td>
+                                <td>{ tx.remitted.display(self.format) }</td>
+                                <td>{ tx.credited.display(self.format) }</td>
+                            </tr>
+                        }
+                    })}
+                </tbody>
+            </table>
+        }
+    }
+
     fn render_remit_bar(&self, kind: CustomerKind) -> Html<Self> {
-        let payable = self.souls;
-        let unit_quantity = cmp::min(Souls(1), payable);
+        let payable = self.state.remittable(kind);
+        let unit_quantity = cmp::min(Souls::new(1), payable);
         let quart_quantity = payable / 4;
         let max_quantity = payable;
 
-        if payable.0 == 0 {
+        if payable.is_zero() {
             return html! {
                 <div class="field has-addons",>
                     <p class="control is-expanded",>
                         <a class="button is-fullwidth is-static",>
-                            {"Can't remit"}
+                            { match kind {
+                                CustomerKind::Heaven => "No virtuous souls to remit",
+                                CustomerKind::Hell => "No sinful souls to remit",
+                            } }
                         </a>
                     </p>
                 </div>
@@ -414,27 +423,39 @@ impl Model {
     }
 
     fn render_remit(&self, kind: CustomerKind, quantity: Souls) -> Html<Self> {
-        if quantity.0 == 0 {
+        if quantity.is_zero() {
             return empty!();
         }
+        let bonus = self.state.remit_bonus();
 
         html! {
             <p class="control is-expanded",>
-                <a class="button is-fullwidth", onclick=|_| Msg::Remit{quantity, target: kind},>
-                    { format!("Remit {}", quantity) }
+                <a class="button is-fullwidth", onclick=|_| Msg::Game(GameMsg::Remit { quantity, target: kind }),>
+                    { format!("Remit {}", quantity.display(self.format)) }
+                    { if bonus > 0.0 {
+                        html! {
+                            <span class="has-text-success",>{ format!("\u{a0}({})", delta_perc(bonus)) }</span>
+                        }
+                    } else { empty!() } }
                 </a>
             </p>
         }
     }
 
     fn render_souls(&self) -> Html<Self> {
-        js! { document.title = @{format!("{} souls - Death Inc.", self.souls)} }
+        let title = format!(
+            "{} souls - Death Inc.",
+            self.state.souls().display(self.format)
+        );
+        js! { document.title = @{title} }
         html! {
             <>
-                <h1 class="title",>{ format!("{} souls", self.souls) }</h1>
-                <h2 class="subtitle",>{ format!("per month: {}", self.souls_per_tick()) }</h2>
+                <h1 class="title",>{ format!("{} souls", self.state.souls().display(self.format)) }</h1>
+                <p class="subtitle is-6",>{ format!("{} virtuous, {} sinful", self.state.virtuous.display(self.format), self.state.sinful.display(self.format)) }</p>
+                <h2 class="subtitle",>{ format!("per month: {}", self.state.souls_per_tick().display(self.format)) }</h2>
+                { self.render_interest() }
                 <div class="content",>
-                    { if self.cheat {
+                    { if self.state.cheat {
                         html! {
                             <div class="message",>
                                 <div class="message-body",>
@@ -445,8 +466,8 @@ impl Model {
                     } else { empty!() } }
                 </div>
 
-                <a class="button is-medium is-danger is-fullwidth", onclick=|_| Msg::Harvest,>
-                    { format!("Harvest {}", self.souls_per_click()) }
+                <a class="button is-medium is-danger is-fullwidth", onclick=|_| Msg::Game(GameMsg::Harvest),>
+                    { format!("Harvest {}", self.state.souls_per_click().display(self.format)) }
                 </a>
 
                 <div style="min-height: 1em",/>
@@ -454,23 +475,56 @@ impl Model {
                 <div class="message",>
                     <div class="message-body",>
                         <p>
-                            { format!("Population: {} ({} / {})", self.alive, delta!(self.births_per_tick() - self.deaths_per_tick()), TICK_UNIT) }
+                            { format!("Population: {} ({} / {})", self.state.alive.display(self.format), delta!(self.state.births_per_tick().saturating_sub(self.state.deaths_per_tick()), self.format), TICK_UNIT) }
                         </p>
                         <p>
-                            { format!("Corpses: {} ({} / {})", self.due, self.deaths_per_tick(), TICK_UNIT) }
+                            { format!("Corpses: {} ({} / {})", self.state.due.display(self.format), self.state.deaths_per_tick().display(self.format), TICK_UNIT) }
                         </p>
                     </div>
                 </div>
 
                 { self.render_extinction() }
                 { self.render_events() }
+                { self.render_reading() }
             </>
         }
     }
 
+    fn render_interest(&self) -> Html<Self> {
+        let rate = self.state.interest_rate();
+        if rate <= 0.0 {
+            return empty!();
+        }
+
+        html! {
+            <h2 class="subtitle is-6",>
+                { format!("interest: {} / {} ({:.2}%)", self.state.interest_per_tick().display(self.format), TICK_UNIT, rate * 100.0) }
+            </h2>
+        }
+    }
+
     fn render_extinction(&self) -> Html<Self> {
-        let delta = self.births_per_tick() - self.deaths_per_tick();
-        if delta < Souls(0) {
+        if self.state.is_extinct() {
+            let disabled = !self.state.can_repopulate();
+            return html! {
+                <div class="message is-danger",>
+                    <div class="message-body",>
+                        <p>
+                            {"Humanity is extinct"}
+                        </p>
+                        <a class="button is-info", disabled=disabled, onclick=|_| Msg::Game(GameMsg::Repopulate),>
+                            { format!("Ask Heaven to repopulate ({} favor)", favor::REPOPULATION_FAVOR.display(self.format)) }
+                        </a>
+                    </div>
+                </div>
+            };
+        }
+
+        let delta = self
+            .state
+            .births_per_tick()
+            .saturating_sub(self.state.deaths_per_tick());
+        if delta < Souls::ZERO {
             html! {
                 <div class="message is-danger",>
                     <div class="message-body",>
@@ -486,31 +540,119 @@ impl Model {
     }
 
     fn render_events(&self) -> Html<Self> {
+        // one modal at a time, the rest wait their turn
+        let mut queue = self.state.queued_events();
         html! {
-            {for self.events.values().filter(|event| !event.consumed).map(|event| {
-                { self.render_event(event) }
-            })}
+            <>
+                { if let Some(event) = queue.next() {
+                    self.render_event(event, queue.count())
+                } else { empty!() } }
+                <div style="position: fixed; bottom: 1em; right: 1em; z-index: 50; max-width: 24em;",>
+                    {for self.state.toasts().map(|event| self.render_toast(event))}
+                </div>
+            </>
         }
     }
 
-    fn render_event(&self, event: &Event) -> Html<Self> {
+    fn render_toast(&self, event: &Event) -> Html<Self> {
         let spec = event.spec;
+        html! {
+            <div class="notification is-info",>
+                <button class="delete", onclick=|_| Msg::Game(GameMsg::ConsumeEvent { spec }),/>
+                <strong>{&spec.name}</strong>
+                { self.render_event_text(spec, event.details.as_ref()) }
+            </div>
+        }
+    }
+
+    fn render_event(&self, event: &Event, waiting: usize) -> Html<Self> {
         html! {
             <div class="modal is-active",>
                 <div class="modal-background",/>
                 <div class="modal-content",>
                     <div class="notification is-info",>
-                        <h3 class="title",>{event.spec.name}</h3>
+                        <h3 class="title",>{&event.spec.name}</h3>
                         <div class="content",>
-                            <p style="white-space: pre-wrap;",>
-                                {event.spec.desc}
-                            </p>
+                            { self.render_event_text(event.spec, event.details.as_ref()) }
+
+                            <div class="level",>
+                                <div class="level-left",>
+                                    { if waiting > 0 {
+                                        html! { <em>{ format!("{} more waiting", waiting) }</em> }
+                                    } else { empty!() } }
+                                </div>
+                                <div class="level-right",>
+                                    { self.render_event_answers(event) }
+                                </div>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_event_text(&self, spec: &EventSpec, details: Option<&Details>) -> Html<Self> {
+        html! {
+            <>
+                <p style="white-space: pre-wrap;",>
+                    {&spec.desc}
+                </p>
+                { if let Some(details) = details {
+                    self.render_details(details)
+                } else { empty!() } }
+            </>
+        }
+    }
+
+    fn render_details(&self, details: &Details) -> Html<Self> {
+        match details {
+            Details::Away(report) => html! {
+                <>
+                    <p>{ format!("You were away for {} {}s.", self.count(report.months), TICK_UNIT) }</p>
+                    <p>
+                        { format!("Souls harvested: {}", report.souls.display(self.format)) }<br/>
+                        { format!("Population change: {}", delta!(report.population, self.format)) }<br/>
+                        { format!("Corpses change: {}", delta!(report.corpses, self.format)) }
+                    </p>
+                </>
+            },
+            Details::Overflow { action } => html! {
+                <p>{ format!("The numbers got out of hand while {}, so we called it off.", action) }</p>
+            },
+        }
+    }
+
+    /// An inbox event, read-only: choices were made the first time around.
+    fn render_reading(&self) -> Html<Self> {
+        let entry = match self
+            .reading
+            .and_then(|id| self.state.inbox.iter().find(|entry| entry.id == id))
+        {
+            Some(entry) => entry,
+            None => return empty!(),
+        };
+
+        html! {
+            <div class="modal is-active",>
+                <div class="modal-background", onclick=|_| Msg::CloseEvent,/>
+                <div class="modal-content",>
+                    <div class="notification",>
+                        <h3 class="title",>{&entry.spec.name}</h3>
+                        <h4 class="subtitle is-6",>{ format!("Received {} {}", TICK_UNIT, entry.month) }</h4>
+                        <div class="content",>
+                            { self.render_event_text(entry.spec, entry.details.as_ref()) }
+                            { if let Some(choice) = entry.chosen {
+                                html! {
+                                    <p><em>{ format!("You answered: {}", choice.label) }</em></p>
+                                }
+                            } else { empty!() } }
 
                             <div class="level",>
                                 <div class="level-left",/>
                                 <div class="level-right",>
-                                    <a class="button is-dark is-inverted is-outlined", onclick=|_| Msg::ConsumeEvent {spec},>
-                                        {"Okay"}
+                                    <a class="button", onclick=|_| Msg::CloseEvent,>
+                                        {"Close"}
                                     </a>
                                 </div>
                             </div>
@@ -521,6 +663,35 @@ impl Model {
         }
     }
 
+    fn render_event_answers(&self, event: &Event) -> Html<Self> {
+        let spec = event.spec;
+        if spec.choices.is_empty() {
+            return html! {
+                <a class="button is-dark is-inverted is-outlined", onclick=|_| Msg::Game(GameMsg::ConsumeEvent { spec }),>
+                    {"Okay"}
+                </a>
+            };
+        }
+
+        html! {
+            <div class="buttons",>
+                {for spec.choices.iter().enumerate().map(|(choice, c)| {
+                    let disabled = !self.state.can_choose(spec, choice);
+                    let cost = c.cost();
+                    html! {
+                        <a class="button is-dark is-inverted is-outlined", disabled=disabled, onclick=|_| Msg::Game(GameMsg::Choose { spec, choice }),>
+                            { if cost > Souls::ZERO {
+                                format!("{} ({} souls)", c.label, cost.display(self.format))
+                            } else {
+                                c.label.clone()
+                            } }
+                        </a>
+                    }
+                })}
+            </div>
+        }
+    }
+
     fn render_tab_switcher(&self) -> Html<Self> {
         html! {
             <div class="tabs is-fullwidth",>
@@ -529,6 +700,8 @@ impl Model {
                 { self.render_tab(Tab::Earth) }
                 { self.render_tab(Tab::Heaven) }
                 { self.render_tab(Tab::Hell) }
+                { self.render_tab(Tab::Inbox) }
+                { self.render_tab(Tab::Settings) }
                 </ul>
             </div>
         }
@@ -538,8 +711,57 @@ impl Model {
         match self.tab {
             Tab::Shop => self.render_shop(),
             Tab::Earth => self.render_earth(),
-            Tab::Heaven => self.render_customer(&self.heaven),
-            Tab::Hell => self.render_customer(&self.hell),
+            Tab::Heaven => self.render_customer(&self.state.heaven),
+            Tab::Hell => self.render_customer(&self.state.hell),
+            Tab::Inbox => self.render_inbox(),
+            Tab::Settings => self.render_settings(),
+        }
+    }
+
+    fn render_inbox(&self) -> Html<Self> {
+        if self.state.inbox.is_empty() {
+            return html! {
+                <p>{"Nothing here yet."}</p>
+            };
+        }
+
+        html! {
+            <table class="table is-fullwidth is-narrow is-hoverable",>
+                <thead>
+                    <tr>
+                        <th>{ "Month" }</th>
+                        <th>{ "Subject" }</th>
+                        <th/>
+                    </tr>
+                </thead>
+                <tbody>
+                    {for self.state.inbox.iter().rev().map(|entry| self.render_inbox_row(entry))}
+                </tbody>
+            </table>
+        }
+    }
+
+    fn render_inbox_row(&self, entry: &InboxEntry) -> Html<Self> {
+        let spec = entry.spec;
+        let id = entry.id;
+        // the ones waiting for an answer are on screen already
+        let disabled = !self.state.can_read(id);
+        html! {
+            <tr>
+                <td>{ entry.month }</td>
+                <td>
+                    { if entry.read {
+                        html! { {&spec.name} }
+                    } else {
+                        html! { <strong>{ format!("{} (unread)", spec.name) }</strong> }
+                    } }
+                </td>
+                <td>
+                    <a class="button is-small", disabled=disabled, onclick=|_| Msg::OpenEvent { entry: id },>
+                        {"Open"}
+                    </a>
+                </td>
+            </tr>
         }
     }
 
@@ -564,6 +786,13 @@ impl Model {
                     Tab::Hell => html! {
                         {"Hell"}
                     },
+                    Tab::Inbox => match self.state.unread_events() {
+                        0 => html! { {"Inbox"} },
+                        unread => html! { { format!("Inbox ({})", unread) } },
+                    },
+                    Tab::Settings => html! {
+                        {"Settings"}
+                    },
                 }
             }</a></li>
         }
@@ -578,7 +807,7 @@ impl Model {
         let logo_class = format!("fa fa-{}", logo);
         let mut count: usize = 0;
         if category == ItemCategory::Upgrades {
-            count = self.visible_upgrades().count();
+            count = self.state.visible_upgrades().count();
         }
 
         html! {
@@ -596,25 +825,13 @@ impl Model {
         }
     }
 
-    fn visible_items<'a>(&'a self, category: ItemCategory) -> impl Iterator<Item = &'a Item> {
-        self.items
-            .values()
-            .filter(move |item| item.revealed && item.spec.category == category)
-    }
-
-    fn visible_upgrades<'a>(&'a self) -> impl Iterator<Item = &'a Upgrade> {
-        self.upgrades
-            .values()
-            .filter(move |up| up.revealed && !up.bought)
-    }
-
     fn render_shop(&self) -> Html<Self> {
         html! {
             <div class="columns",>
                 <div class="column",>
                     { if self.item_category == ItemCategory::Upgrades {
                         html! {
-                            {for self.upgrades.values().filter(|u| u.revealed && !u.bought).map(|upgrade| {
+                            {for self.state.upgrades.values().filter(|u| u.revealed && !u.bought).map(|upgrade| {
                                 self.render_upgrade(upgrade)
                             })}
                         }
@@ -622,14 +839,15 @@ impl Model {
                     } else {
                         html! {
                             <>
-                                { if self.visible_items(self.item_category).next().is_none() {
+                                { if self.state.visible_items(self.item_category).next().is_none() {
                                     html! {
                                         <p>{"Nothing to buy for now..."}</p>
                                     }
                                 } else { empty!() } }
-                                {for self.visible_items(self.item_category).map(|item| {
+                                {for self.state.visible_items(self.item_category).map(|item| {
                                     self.render_item(item)
                                 })}
+                                { self.render_done_items() }
                             </>
                         }
                     } }
@@ -655,26 +873,47 @@ impl Model {
             <div class="box",>
                 <div class="subtitle",>
                     <div class="level-left",>
-                        { upgrade.spec.name }
+                        { &upgrade.spec.name }
                     </div>
                 </div>
                 <div class="content", style="white-space: pre-wrap",>
-                    { upgrade.spec.desc }
+                    { &upgrade.spec.desc }
                 </div>
-                    { if upgrade.bought {
-                        html! {
-                            <a class="button", disabled=true,>
-                                {"Bought."}
-                            </a>
-                        }
-                    } else {
+                { if upgrade.bought {
+                    html! {
+                        <a class="button", disabled=true,>
+                            {"Bought."}
+                        </a>
+                    }
+                } else {
+                    let disabled = !self.state.can_buy_upgrade(spec);
+                    html! {
+                        <a class="button is-danger", disabled=disabled, onclick=|_| Msg::Game(GameMsg::PurchaseUpgrade { spec }),>
+                            { format!("Purchase ({} souls)", spec.cost.display(self.format)) }
+                        </a>
+                    }
+                } }
+            </div>
+        }
+    }
+
+    fn render_done_items(&self) -> Html<Self> {
+        if self.state.done_items(self.item_category).next().is_none() {
+            return empty!();
+        }
+
+        html! {
+            <div class="content has-text-grey",>
+                <p>
+                    <strong class="has-text-grey",>{"Done"}</strong>
+                </p>
+                <ul>
+                    {for self.state.done_items(self.item_category).map(|item| {
                         html! {
-                            <a class="button", onclick=|_| Msg::PurchaseUpgrade { spec },>
-                                {"Purchase"}
-                            </a>
+                            <li>{ item.name() }</li>
                         }
-                    } }
-                </div>
+                    })}
+                </ul>
             </div>
         }
     }
@@ -694,8 +933,10 @@ impl Model {
                     { self.render_item_desc(item) }
                     { self.render_item_souls_per_click(item) }
                     { self.render_item_souls_per_tick(item) }
+                    { self.render_item_interest(item) }
                     { self.render_item_birth_rate(item) }
                     { self.render_item_death_rate(item) }
+                    { self.render_item_goodness(item) }
                 </div>
                 { self.render_item_buybar(item) }
             </div>
@@ -708,57 +949,72 @@ impl Model {
         }
 
         html! {
-            { format!(" x{}", item.quantity) }
+            { format!(" x{}", self.count(item.quantity)) }
         }
     }
 
     fn render_item_buybar(&self, item: &Item) -> Html<Self> {
         if item.spec.unique {
-            if item.quantity > 0 {
-                html! {
-                    <div class="field has-addons",>
-                        <p class="control is-expanded",>
-                            <a class="button is-static is-fullwidth",>
-                                {"Bought"}
-                            </a>
-                        </p>
-                    </div>
-                }
-            } else {
-                html! {
-                    <div class="field has-addons",>
-                        { self.render_item_purchase(item, 1) }
-                    </div>
-                }
-            }
-        } else {
             html! {
                 <div class="field has-addons",>
-                    { self.render_item_purchase(item, 1) }
-                    { self.render_item_purchase(item, 10) }
-                    // { self.render_item_purchase(item, 100) }
+                    { self.render_item_trigger(item) }
                 </div>
             }
+        } else {
+            html! {
+                <>
+                    <div class="field has-addons",>
+                        { self.render_item_purchase(item, 1) }
+                        { self.render_item_purchase(item, 10) }
+                        { self.render_item_purchase_max(item) }
+                    </div>
+                    { self.render_item_sellbar(item) }
+                </>
+            }
+        }
+    }
+
+    fn render_item_sellbar(&self, item: &Item) -> Html<Self> {
+        if !item.can_sell() {
+            return empty!();
+        }
+
+        html! {
+            <div class="field has-addons",>
+                { self.render_item_sell(item, 1, "Sell 1") }
+                { self.render_item_sell(item, item.quantity, "Sell all") }
+            </div>
+        }
+    }
+
+    fn render_item_sell(&self, item: &Item, quantity: i64, label: &str) -> Html<Self> {
+        let spec = item.spec;
+        html! {
+            <p class="control is-expanded",>
+                <a class="button is-light is-fullwidth", onclick=|_| Msg::Game(GameMsg::Sell { quantity, spec }),>
+                    {format!("{} (+{} souls)", label, item.refund_n(quantity).display(self.format))}
+                </a>
+            </p>
         }
     }
 
     fn render_item_desc(&self, item: &Item) -> Html<Self> {
         let spec = item.spec;
-        if spec.desc == "" {
+        if spec.desc.is_empty() {
             return empty!();
         }
 
         html! {
-            <p>{ spec.desc }</p>
+            <p>{ &spec.desc }</p>
         }
     }
 
     fn render_item_souls_per_click(&self, item: &Item) -> Html<Self> {
-        if let Some(x) = item.spec.get_spc(self) {
+        if let Some(x) = item.spec.get_spc(&self.state) {
             html! {
                 <p>
-                    { format!("Harvests {} souls / click. ", x.effective) }
-                    { format!(" (×{:.3} bonus, contributes {} SpC)", x.bonus, x.multiply(item.quantity)) }
+                    { format!("Harvests {} souls / click. ", x.effective.display(self.format)) }
+                    { format!(" (×{:.3} bonus, contributes {} SpC)", x.bonus, x.multiply(item.quantity).display(self.format)) }
                 </p>
             }
         } else {
@@ -767,11 +1023,24 @@ impl Model {
     }
 
     fn render_item_souls_per_tick(&self, item: &Item) -> Html<Self> {
-        if let Some(x) = item.spec.get_spt(self) {
+        if let Some(x) = item.spec.get_spt(&self.state) {
             html! {
                 <p>
-                    { format!("Harvests {} souls / {}.", x.effective, TICK_UNIT) }
-                    { format!(" (×{:.3} bonus, contributes {} SpM)", x.bonus, x.multiply(item.quantity)) }
+                    { format!("Harvests {} souls / {}.", x.effective.display(self.format), TICK_UNIT) }
+                    { format!(" (×{:.3} bonus, contributes {} SpM)", x.bonus, x.multiply(item.quantity).display(self.format)) }
+                </p>
+            }
+        } else {
+            empty!()
+        }
+    }
+
+    fn render_item_interest(&self, item: &Item) -> Html<Self> {
+        if let Some(rate) = item.spec.get_interest(&self.state) {
+            html! {
+                <p>
+                    { format!("Pays {:.2}% interest every {}.", rate * 100.0, TICK_UNIT) }
+                    { format!(" (contributes {:.2}%)", rate * item.quantity as f64 * 100.0) }
                 </p>
             }
         } else {
@@ -807,19 +1076,141 @@ impl Model {
         }
     }
 
+    fn render_item_goodness(&self, item: &Item) -> Html<Self> {
+        let spec = item.spec;
+
+        if let Some(q) = spec.goodness_mod {
+            html! {
+                <p>
+                    { format!("Effect: Virtue {:+.1}%", q * 100.0) }
+                </p>
+            }
+        } else {
+            empty!()
+        }
+    }
+
+    fn render_item_trigger(&self, item: &Item) -> Html<Self> {
+        let spec = item.spec;
+        let cost = item.cost();
+        let disabled =
+            item.is_done() || cost > self.state.souls() || !spec.favor_allows(&self.state);
+        html! {
+            <p class="control is-expanded",>
+                <a class="button is-warning is-fullwidth", disabled=disabled, onclick=|_| Msg::Game(GameMsg::Purchase { quantity: 1, spec }),>
+                    {format!("Trigger ({} souls)", cost.display(self.format))}
+                </a>
+            </p>
+        }
+    }
+
     fn render_item_purchase(&self, item: &Item, quantity: i64) -> Html<Self> {
         let spec = item.spec;
         let cost = item.cost_n(quantity);
-        let disabled = cost > self.souls;
+        let disabled = cost > self.state.souls() || !spec.favor_allows(&self.state);
         html! {
             <p class="control is-expanded",>
-                <a class="button is-danger is-fullwidth", disabled=disabled, onclick=|_| Msg::Purchase {quantity, spec},>
-                    {format!("Buy {} ({} souls)", quantity, cost)}
+                <a class="button is-danger is-fullwidth", disabled=disabled, onclick=|_| Msg::Game(GameMsg::Purchase { quantity, spec }),>
+                    {format!("Buy {} ({} souls)", quantity, cost.display(self.format))}
                 </a>
             </p>
         }
     }
 
+    fn render_item_purchase_max(&self, item: &Item) -> Html<Self> {
+        let spec = item.spec;
+        let quantity = item.affordable(self.state.souls());
+        let disabled = quantity == 0 || !spec.favor_allows(&self.state);
+        html! {
+            <p class="control is-expanded",>
+                <a class="button is-danger is-fullwidth", disabled=disabled, onclick=|_| Msg::Game(GameMsg::Purchase { quantity, spec }),>
+                    { if quantity == 0 {
+                        "Buy max".to_owned()
+                    } else {
+                        format!("Buy max: {} ({} souls)", self.count(quantity), item.cost_n(quantity).display(self.format))
+                    } }
+                </a>
+            </p>
+        }
+    }
+
+    fn render_settings(&self) -> Html<Self> {
+        // shows off what each option looks like
+        let sample = Souls::new(1_234_567_890);
+
+        html! {
+            <div class="content",>
+                <p>
+                    <strong>{"Number format"}</strong>
+                </p>
+                <div class="field is-grouped",>
+                    <div class="control",>
+                        <div class="select",>
+                            <select onchange=|e| match e {
+                                ChangeData::Select(el) => Msg::SelectNotation(el.value().unwrap_or_default()),
+                                _ => Msg::SelectNotation(String::new()),
+                            },>
+                                {for Notation::ALL.iter().map(|&notation| {
+                                    let format = NumberFormat { notation, ..self.format };
+                                    html! {
+                                        <option value=notation.key(), selected=notation == self.format.notation,>
+                                            { format!("{} ({})", notation.name(), sample.display(format)) }
+                                        </option>
+                                    }
+                                })}
+                            </select>
+                        </div>
+                    </div>
+                    <div class="control",>
+                        <div class="select",>
+                            <select onchange=|e| match e {
+                                ChangeData::Select(el) => Msg::SelectLocale(el.value().unwrap_or_default()),
+                                _ => Msg::SelectLocale(String::new()),
+                            },>
+                                {for LOCALES.iter().map(|&locale| {
+                                    let format = NumberFormat { notation: Notation::Full, locale };
+                                    html! {
+                                        <option value=locale.name(), selected=locale == self.format.locale,>
+                                            { format!("{} ({})", locale.name(), sample.display(format)) }
+                                        </option>
+                                    }
+                                })}
+                            </select>
+                        </div>
+                    </div>
+                </div>
+
+                <p>
+                    <strong>{"Export save"}</strong>
+                </p>
+                { if let Some(exported) = self.exported.as_ref() {
+                    html! {
+                        <textarea class="textarea", readonly=true, value=exported,/>
+                    }
+                } else { empty!() } }
+                <a class="button", onclick=|_| Msg::ExportSave,>
+                    {"Export save"}
+                </a>
+
+                <p>
+                    <strong>{"Import save"}</strong>
+                </p>
+                <textarea class="textarea", placeholder="Paste an exported save here",
+                    value=&self.import_text, oninput=|e| Msg::UpdateImport(e.value),/>
+                { if let Some(error) = self.import_error.as_ref() {
+                    html! {
+                        <div class="message is-danger",>
+                            <div class="message-body",>{ error }</div>
+                        </div>
+                    }
+                } else { empty!() } }
+                <a class="button is-danger", disabled=self.import_text.is_empty(), onclick=|_| Msg::ImportSave,>
+                    {"Import save"}
+                </a>
+            </div>
+        }
+    }
+
     fn render_earth(&self) -> Html<Self> {
         html! {
             <>
@@ -828,159 +1219,95 @@ impl Model {
                         <strong>{"Earth"}</strong>
                     </p>
                     <p>
-                        { format!("{:.0}% of the population is virtuous.", (self.goodness*100.0)) }
+                        { format!("{:.0}% of the population is virtuous", (self.state.goodness*100.0)) }
+                        { format!(" (trending towards {:.0}%).", self.state.target_goodness()*100.0) }
                     </p>
 
                     <p>
-                        { format!("There are {} humans alive right now.", self.alive) }
+                        { format!("There are {} humans alive right now.", self.state.alive.display(self.format)) }
                     </p>
                     <p>
-                        { format!("{} humans are born every {}. (Rate: {:.2} / year / 1000 population)", self.births_per_tick(), TICK_UNIT, self.effective_birth_rate()) }
+                        { format!("{} humans are born every {}. (Rate: {:.2} / year / 1000 population)", self.state.births_per_tick().display(self.format), TICK_UNIT, self.state.effective_birth_rate()) }
                     </p>
                     <p>
-                        { format!("{} humans expire every {}. (Rate {:.2} / year / 1000 population)", self.deaths_per_tick(), TICK_UNIT, self.effective_death_rate()) }
+                        { format!("{} humans expire every {}. (Rate {:.2} / year / 1000 population)", self.state.deaths_per_tick().display(self.format), TICK_UNIT, self.state.effective_death_rate()) }
                     </p>
+                    {for self.state.modifiers.iter().map(|m| self.render_modifier(m))}
                 </div>
             </>
         }
     }
 
-    fn effective_birth_rate(&self) -> f64 {
-        self.base_birth_rate * self.sum_factor(|i| i.spec.br_mod)
-    }
-
-    fn effective_death_rate(&self) -> f64 {
-        self.base_death_rate * self.sum_factor(|i| i.spec.dr_mod)
-    }
-
-    fn sum_factor(&self, f: fn(item: &Item) -> Option<f64>) -> f64 {
-        let mut factor = 1.0;
-        for item in self.items.values() {
-            if let Some(q) = f(item) {
-                factor += q * item.quantity as f64;
-            }
-        }
-        factor
-    }
-
-    fn births_per_tick(&self) -> Souls {
-        Souls(
-            (self.alive.float() / 1000.0 * self.effective_birth_rate() / DAYS_PER_YEAR
-                * DAYS_PER_TICK)
-                .ceil() as i64,
-        )
-    }
-
-    fn deaths_per_tick(&self) -> Souls {
-        Souls(
-            (self.alive.float() / 1000.0 * self.effective_death_rate() / DAYS_PER_YEAR
-                * DAYS_PER_TICK)
-                .ceil() as i64,
-        )
-    }
-
-    fn souls_per_tick(&self) -> Souls {
-        let mut total = Souls(0);
-        for item in self.items.values() {
-            if let Some(x) = item.spec.get_spt(self) {
-                total += x.multiply(item.quantity);
-            }
+    fn render_modifier(&self, modifier: &Modifier) -> Html<Self> {
+        let mut effects = Vec::new();
+        if modifier.birth != 0.0 {
+            effects.push(format!("Birth rate {}", delta_perc(modifier.birth)));
         }
-        total
-    }
-
-    fn souls_per_click(&self) -> Souls {
-        if self.cheat {
-            return Souls::B;
-        }
-
-        let mut total = Souls(1);
-        for item in self.items.values() {
-            if let Some(x) = item.spec.get_spc(self) {
-                total += x.multiply(item.quantity);
-            }
+        if modifier.death != 0.0 {
+            effects.push(format!("Death rate {}", delta_perc(modifier.death)));
         }
-        total
-    }
-
-    #[allow(dead_code)]
-    fn item_quantity(&self, item: &ItemSpec) -> i64 {
-        if let Some(item) = self.items.get(item) {
-            item.quantity
-        } else {
-            0
+        html! {
+            <p>
+                <em>{ format!("{} until {} {}.", effects.join(", "), TICK_UNIT, modifier.until) }</em>
+            </p>
         }
     }
+}
 
-    fn harvest(&mut self, quantity: Souls) {
-        let harvested = cmp::min(self.alive, cmp::min(self.due, quantity));
-        self.due -= harvested;
-        self.souls += harvested;
-    }
-
-    fn update_items_reveal(&mut self) {
-        for item in self.items.values_mut() {
-            if !item.revealed {
-                item.revealed = {
-                    if item.quantity > 0 {
-                        true
-                    } else if self.souls >= item.spec.cost / 2 {
-                        true
-                    } else {
-                        false
-                    }
-                };
-            }
-        }
-    }
+fn restore(storage: &mut StorageService) -> GameState {
+    let data: Text = storage.restore(SAVE_KEY);
+    let json = match data {
+        Ok(json) => json,
+        Err(_) => return GameState::with_seed(now() as u64),
+    };
 
-    fn update_upgrades_reveal(&mut self) {
-        for upgrade in self.upgrades.values_mut() {
-            if !upgrade.revealed {
-                upgrade.revealed = {
-                    if self.souls >= upgrade.spec.cost / 2 {
-                        true
-                    } else {
-                        false
-                    }
-                };
-            }
+    match save::from_json(&json) {
+        Ok(state) => state,
+        Err(e) => {
+            log(&format!("Could not load save, starting over: {}", e));
+            // keep it around, in case it can be salvaged by hand
+            storage.store(BAD_SAVE_KEY, Ok(json) as Text);
+            GameState::with_seed(now() as u64)
         }
     }
+}
 
-    fn apply_buy_effects(&mut self, spec: &ItemSpec, new_quantity: i64) {
-        if let Some(mult) = spec.pop_multiplier {
-            self.alive = Souls(((self.alive.0 as f64) * mult) as i64);
-        }
-        if let Some(r) = spec.pop_kill_ratio {
-            let deaths = Souls(((self.alive.0 as f64) * r) as i64);
-            self.alive -= deaths;
-            self.due += deaths;
+fn restore_format(storage: &mut StorageService) -> NumberFormat {
+    let mut format = NumberFormat::default();
+    let data: Text = storage.restore(FORMAT_KEY);
+    if let Ok(data) = data {
+        let mut parts = data.split(' ');
+        if let Some(notation) = parts.next().and_then(Notation::from_key) {
+            format.notation = notation;
         }
-
-        if spec.id == items::Bailiff.id && new_quantity == 1 {
-            let ev = events::HelloFromHell.instantiate();
-            self.events.insert(ev.spec, ev);
+        if let Some(Ok(locale)) = parts.next().map(Locale::from_name) {
+            format.locale = locale;
         }
     }
+    format
+}
 
-    fn has_active_events(&self) -> bool {
-        self.events
-            .values()
-            .filter(|ev| !ev.consumed)
-            .next()
-            .is_some()
+/// Fast-forwards through the months that would have elapsed since the
+/// game was last saved, had the tab stayed open.
+fn catch_up(state: &mut GameState) {
+    if let Some(saved_at) = state.saved_at {
+        let elapsed = now() - saved_at;
+        let months = cmp::min(elapsed / tick_millis() as i64, MAX_OFFLINE_MONTHS);
+        if months > 0 {
+            state.fast_forward(months);
+        }
     }
+}
 
-    fn apply_upgrade(&mut self, spec: &'static UpgradeSpec) {
-        for effect in spec.effects.as_slice() {
-            if !self.effects.contains_key(effect.spec) {
-                self.effects.insert(effect.spec, Vec::new());
-            }
+fn now() -> i64 {
+    Date::now() as i64
+}
 
-            let list = self.effects.get_mut(effect.spec).unwrap();
-            list.push(effect);
-        }
+fn tick_millis() -> u64 {
+    if cheat_enabled() {
+        50
+    } else {
+        100
     }
 }
 